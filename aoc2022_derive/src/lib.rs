@@ -0,0 +1,191 @@
+//! Derive macros for the aoc2022 crate.
+//!
+//! The generated code refers to `crate::utils::from_line`, so these macros are only usable inside aoc2022.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use regex::Regex;
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, GenericArgument, LitStr,
+    PathArguments, Type,
+};
+
+/**
+Implement `FromLine` for a struct, filling its fields from the captures of a regex.
+
+```ignore
+#[derive(FromLine)]
+#[pattern(r"^move (\d+) from (\d+) to (\d+)$")]
+struct Instruction {
+    count: usize,
+    from: usize,
+    to: usize,
+}
+```
+
+Fields are filled from capture groups in order, or by name if the pattern has named groups (`(?P<count>\d+)`).
+Each field is parsed with `FromStr`, except:
+ - `Vec<T>` fields, which are parsed from a comma separated list
+ - `Option<T>` fields, which are `None` if their group didn't match
+
+The pattern is checked at compile time.
+*/
+#[proc_macro_derive(FromLine, attributes(pattern))]
+pub fn derive_from_line(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_line(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// How a field's type is parsed from its capture
+enum FieldKind {
+    Single,
+    List,
+    Optional,
+}
+
+fn from_line(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let pattern = find_pattern(input)?;
+    let regex = Regex::new(&pattern.value())
+        .map_err(|err| Error::new(pattern.span(), format!("invalid pattern: {}", err)))?;
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "FromLine can only be derived for structs",
+            ))
+        }
+    };
+
+    let named_groups = regex.capture_names().flatten().collect::<Vec<_>>();
+    let group_count = regex.captures_len() - 1;
+
+    if named_groups.is_empty() && group_count != fields.len() {
+        return Err(Error::new(
+            pattern.span(),
+            format!(
+                "pattern has {} capture groups, but there are {} fields",
+                group_count,
+                fields.len()
+            ),
+        ));
+    }
+
+    let mut values = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let (group, description) = match (&field.ident, named_groups.is_empty()) {
+            (Some(ident), false) => {
+                let name = ident.to_string();
+                if !named_groups.contains(&name.as_str()) {
+                    return Err(Error::new(
+                        ident.span(),
+                        format!("pattern has no capture group named '{}'", name),
+                    ));
+                }
+                (quote!(captures.name(#name)), name)
+            }
+            (None, false) => {
+                return Err(Error::new(
+                    pattern.span(),
+                    "tuple structs can't use named capture groups",
+                ))
+            }
+            (ident, true) => {
+                let index = i + 1;
+                let description = match ident {
+                    Some(ident) => ident.to_string(),
+                    None => i.to_string(),
+                };
+                (quote!(captures.get(#index)), description)
+            }
+        };
+
+        let parse = match field_kind(&field.ty) {
+            FieldKind::Single => quote!(parse_field),
+            FieldKind::List => quote!(parse_list),
+            FieldKind::Optional => quote!(parse_optional),
+        };
+        let value = quote!(crate::utils::from_line::#parse(#group, #description)?);
+
+        values.push(match &field.ident {
+            Some(ident) => quote!(#ident: #value),
+            None => value,
+        });
+    }
+
+    let constructor = match fields {
+        Fields::Named(_) => quote!(Self { #(#values),* }),
+        Fields::Unnamed(_) => quote!(Self(#(#values),*)),
+        Fields::Unit => quote!(Self),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics crate::utils::from_line::FromLine for #name #ty_generics #where_clause {
+            const PATTERN: &'static str = #pattern;
+
+            fn from_captures(captures: &::regex::Captures) -> ::anyhow::Result<Self> {
+                Ok(#constructor)
+            }
+        }
+    })
+}
+
+/// the string literal in `#[pattern(r"...")]`
+fn find_pattern(input: &DeriveInput) -> syn::Result<LitStr> {
+    let mut patterns = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("pattern"));
+
+    let pattern = patterns.next().ok_or_else(|| {
+        Error::new(
+            input.ident.span(),
+            "FromLine needs a #[pattern(r\"...\")] attribute",
+        )
+    })?;
+
+    if let Some(duplicate) = patterns.next() {
+        return Err(Error::new(
+            duplicate.span(),
+            "FromLine takes a single #[pattern] attribute",
+        ));
+    }
+
+    pattern.parse_args::<LitStr>()
+}
+
+/// `Vec<T>` and `Option<T>` fields are parsed specially, going by the name of the outer type
+fn field_kind(ty: &Type) -> FieldKind {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last(),
+        _ => None,
+    };
+
+    match segment {
+        Some(segment) if has_one_type_argument(&segment.arguments) => {
+            match segment.ident.to_string().as_str() {
+                "Vec" => FieldKind::List,
+                "Option" => FieldKind::Optional,
+                _ => FieldKind::Single,
+            }
+        }
+        _ => FieldKind::Single,
+    }
+}
+
+fn has_one_type_argument(arguments: &PathArguments) -> bool {
+    match arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            arguments.args.len() == 1
+                && matches!(arguments.args.first(), Some(GenericArgument::Type(_)))
+        }
+        _ => false,
+    }
+}
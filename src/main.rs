@@ -1,14 +1,24 @@
-use std::{fs, path::Path};
+use std::{
+    fs,
+    io::{stdin, stdout},
+    path::Path,
+};
 
 use anyhow::{anyhow, Ok, Result};
 use clap::{Parser, ValueEnum};
-use solutions::SOLUTIONS;
+use itertools::Itertools;
+use solutions::{Implementation, EXAMPLES, SIMULATIONS, SOLUTIONS};
 
 use crate::{
     solutions::templates::{
         linear_template::ExampleSolutionLinear, simultaneous_template::ExampleSolutionSimultaneous,
     },
-    utils::solver_types::{solve_linear, solve_simultaneous},
+    utils::{
+        input_error::InputError,
+        progress::{cancel_on_ctrlc, Cancelled},
+        simulation::debug_simulation,
+        solver_types::{solve_linear, solve_simultaneous, Solved},
+    },
 };
 
 pub mod solutions;
@@ -25,38 +35,90 @@ enum RunMode {
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[arg(long, short, default_value = "example")]
-    /// Example: run an example. Single: run a single day's solution. All: Run all solutions sequentially.
+    /// Example: run an example, or check a day's worked examples if --day is given. Single: run a single day's solution. All: Run all solutions sequentially.
     mode: RunMode,
-    /// Specify which day's solution to run - only used when --mode is single.
+    /// Specify which day's solution to run - used when --mode is single or example.
     #[arg(long, short, required_if_eq("mode", "single"))]
     day: Option<usize>,
     /// Specify the filepath to the day's input - only used when --mode is single
     #[arg(long, short)]
     input: Option<String>,
+    /// Step through the day's simulation interactively instead of solving it - only used when --mode is single
+    #[arg(long)]
+    step: bool,
+    /// Specify which part's simulation to step through - only used with --step
+    #[arg(long, short, default_value_t = 1)]
+    part: usize,
+    /// Specify which implementation of the day's solution to run, if it has several - only used when --mode is single
+    #[arg(long = "impl")]
+    implementation: Option<String>,
+    /// Run every implementation of the day's solution, check they agree and compare their timings - only used when --mode is single
+    #[arg(long)]
+    compare: bool,
 }
 
 fn main() {
     let args = Args::parse();
 
+    // the debugger has its own quit command, so leave Ctrl-C to stop it straight away
+    let stepping = matches!(args.mode, RunMode::Single) && args.step;
+    if !stepping {
+        if let Err(err) = cancel_on_ctrlc() {
+            println!("Ctrl-C will not cancel solutions cleanly. Reason: {}", err)
+        }
+    }
+
     match args.mode {
-        RunMode::Example => run_example(),
+        RunMode::Example => match args.day {
+            None => run_example(),
+            Some(day) => {
+                if let Err(err) = run_day_examples(day) {
+                    println!("Examples for Day {} failed. Reason: {:#}", day, err)
+                }
+            }
+        },
         RunMode::All => {
             let result = run_all();
             if let Err(err) = result {
-                println!("Failed to run solutions. Reason: {}", err)
+                println!("Failed to run solutions. Reason: {:#}", err)
             }
         }
-        RunMode::Single => {
-            let result = run_single(args.day.unwrap(), args.input);
+        RunMode::Single if args.step => {
+            let result = run_step(args.day.unwrap(), args.part, args.input);
+
+            if let Err(err) = result {
+                println!(
+                    "Failed to step through Day {}. Reason: {:#}",
+                    args.day.unwrap(),
+                    err
+                )
+            }
+        }
+        RunMode::Single if args.compare => {
+            let result = run_compare(args.day.unwrap(), args.input);
 
             if let Err(err) = result {
                 println!(
-                    "Failed to run solution for Day {}. Reason: {}",
+                    "Failed to compare solutions for Day {}. Reason: {:#}",
                     args.day.unwrap(),
                     err
                 )
             }
         }
+        RunMode::Single => {
+            let result = run_single(args.day.unwrap(), args.input, args.implementation);
+
+            if let Err(err) = result {
+                match err.downcast_ref::<Cancelled>() {
+                    Some(cancelled) => println!("Day {} was {}", args.day.unwrap(), cancelled),
+                    None => println!(
+                        "Failed to run solution for Day {}. Reason: {:#}",
+                        args.day.unwrap(),
+                        err
+                    ),
+                }
+            }
+        }
     }
 }
 
@@ -81,18 +143,161 @@ fn load_from_file(file_path: &Path) -> Result<String> {
     }
 }
 
-/// run a single specified day's solution
-fn run_single(day: usize, input_path: Option<String>) -> Result<f32> {
+/// get the implementations of a day's solution
+fn implementations(day: usize) -> Result<&'static [Implementation]> {
     if day < 1 || day > SOLUTIONS.len() {
         return Err(anyhow!("Day '{}' is invalid or not yet solved", day));
     }
 
+    Ok(SOLUTIONS[day - 1])
+}
+
+/// run a single specified day's solution, using the named implementation or the default
+fn run_single(
+    day: usize,
+    input_path: Option<String>,
+    implementation: Option<String>,
+) -> Result<Solved> {
+    let implementations = implementations(day)?;
+
+    let (_, solution) = match implementation {
+        None => implementations[0],
+        Some(name) => *implementations
+            .iter()
+            .find(|(impl_name, _)| *impl_name == name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Day '{}' has no implementation '{}'. Available: {}",
+                    day,
+                    name,
+                    implementations.iter().map(|(name, _)| name).join(", ")
+                )
+            })?,
+    };
+
+    let (path, input) = load_input(day, input_path)?;
+
+    solution(&input).map_err(|err| render_input_error(err, &path, &input))
+}
+
+/// run every implementation of a day's solution on the same input,
+/// checking that they agree and comparing how long they took
+fn run_compare(day: usize, input_path: Option<String>) -> Result<()> {
+    let implementations = implementations(day)?;
+    let (path, input) = load_input(day, input_path)?;
+
+    let mut results = Vec::new();
+    for (name, solution) in implementations {
+        println!("Running implementation '{}':", name);
+        let solved = solution(&input).map_err(|err| render_input_error(err, &path, &input))?;
+        results.push((name, solved));
+        println!();
+    }
+
+    let (_, expected) = &results[0];
+    if results
+        .iter()
+        .any(|(_, solved)| solved.part1 != expected.part1 || solved.part2 != expected.part2)
+    {
+        return Err(anyhow!(
+            "Implementations disagree:\n{}",
+            results
+                .iter()
+                .map(|(name, solved)| format!(
+                    "{}:\tpart 1 = {}, part 2 = {}",
+                    name, solved.part1, solved.part2
+                ))
+                .join("\n")
+        ));
+    }
+
+    println!(
+        "All {} implementations agree: part 1 = {}, part 2 = {}",
+        results.len(),
+        expected.part1,
+        expected.part2
+    );
+
+    let fastest = results
+        .iter()
+        .map(|(_, solved)| solved.time)
+        .fold(f32::INFINITY, f32::min);
+    for (name, solved) in &results {
+        println!(
+            "{}:\t{}ms\t({:.2}x the fastest)",
+            name,
+            solved.time,
+            solved.time / fastest
+        );
+    }
+
+    Ok(())
+}
+
+/// load the input for a day, falling back to the default input path.
+/// Returns the path it was loaded from along with the input
+fn load_input(day: usize, input_path: Option<String>) -> Result<(String, String)> {
     let unwrapped_path = input_path.unwrap_or(format!("./inputs/input_{:02}.txt", day));
-    let file_path = Path::new(&unwrapped_path);
+    let input = load_from_file(Path::new(&unwrapped_path))?;
+
+    Ok((unwrapped_path, input))
+}
+
+/// if the error was caused by bad input, replace it with the offending line of the input and a caret
+fn render_input_error(err: anyhow::Error, path: &str, input: &str) -> anyhow::Error {
+    match InputError::find(&err) {
+        Some(input_err) => anyhow!("{}", input_err.render(path, input)),
+        None => err,
+    }
+}
+
+/// interactively step through a single day's simulation
+fn run_step(day: usize, part: usize, input_path: Option<String>) -> Result<()> {
+    let build = SIMULATIONS
+        .iter()
+        .find(|(sim_day, _)| *sim_day == day)
+        .map(|(_, build)| build)
+        .ok_or_else(|| {
+            anyhow!(
+                "Day '{}' has no simulation to step through. Days with simulations: {:?}",
+                day,
+                SIMULATIONS
+                    .iter()
+                    .map(|(sim_day, _)| sim_day)
+                    .collect::<Vec<_>>()
+            )
+        })?;
+
+    let (path, input) = load_input(day, input_path)?;
+    let mut sim = build(&input, part).map_err(|err| render_input_error(err, &path, &input))?;
 
-    let input = load_from_file(file_path)?;
+    debug_simulation(sim.as_mut(), stdin().lock(), stdout())
+}
+
+/// check a day's solution against the worked examples from the puzzle
+fn run_day_examples(day: usize) -> Result<()> {
+    if day < 1 || day > EXAMPLES.len() {
+        return Err(anyhow!("Day '{}' is invalid or not yet solved", day));
+    }
 
-    SOLUTIONS[day - 1](&input)
+    let examples = EXAMPLES[day - 1];
+    let mut failed = 0;
+    for (i, example) in examples.iter().enumerate() {
+        match example() {
+            Result::Ok(()) => println!("Example {}:\tok", i + 1),
+            Err(err) => {
+                failed += 1;
+                println!("Example {}:\tFAILED, {}", i + 1, err)
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow!("{} of {} examples failed", failed, examples.len()));
+    }
+
+    println!("All {} examples passed", examples.len());
+    Ok(())
 }
 
 /// run all solutions
@@ -100,7 +305,7 @@ fn run_all() -> Result<()> {
     let mut time_total = 0.0;
 
     for i in 0..SOLUTIONS.len() {
-        time_total += run_single(i + 1, None)?;
+        time_total += run_single(i + 1, None, None)?.time;
     }
 
     println!("Overall runtime: {}ms", time_total);
@@ -132,5 +337,5 @@ part 2: get the number after the first number that's higher than the previous
     let solved_in_2 =
         solve_simultaneous::<ExampleSolutionSimultaneous, _, _, _>("[6,5,4,2,3,5,8]").unwrap();
 
-    println!("Example time:\t\t{}ms", solved_in_1 + solved_in_2);
+    println!("Example time:\t\t{}ms", solved_in_1.time + solved_in_2.time);
 }
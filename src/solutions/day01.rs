@@ -1,11 +1,15 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::{
+    input_error::InputError,
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
 use anyhow::Result;
 use itertools::Itertools;
 
 /// Not yet implementd
 pub struct Day1Solution {}
 
-pub fn day01(input: &str) -> Result<f32> {
+pub fn day01(input: &str) -> Result<Solved> {
     solve_linear::<Day1Solution, _, _, _>(input)
 }
 
@@ -17,9 +21,12 @@ impl SolutionLinear<Vec<Vec<i32>>, i32, i32> for Day1Solution {
         let mut inventories = Vec::new();
         let mut inventory = Vec::new();
 
-        for line in _input.lines() {
+        for (i, line) in _input.lines().enumerate() {
             if line != "" {
-                inventory.push(line.parse::<i32>().unwrap());
+                let calories = line.parse::<i32>().map_err(|err| {
+                    InputError::at_line(_input, i + 1, format!("Expected a calorie count: {}", err))
+                })?;
+                inventory.push(calories);
             } else {
                 if inventory.len() != 0 {
                     inventories.push(inventory);
@@ -50,14 +57,8 @@ impl SolutionLinear<Vec<Vec<i32>>, i32, i32> for Day1Solution {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day1Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day1Solution;
+    (
         "1000
 2000
 3000
@@ -75,13 +76,5 @@ mod tests {
 ",
         24000,
         45000
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day1Solution::load(input).unwrap();
-        let p1 = Day1Solution::part1(&mut input).unwrap();
-        let p2 = Day1Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
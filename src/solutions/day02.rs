@@ -1,6 +1,9 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::{
+    input_error::InputError,
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
 use anyhow::{anyhow, Result};
-use itertools::Itertools;
 
 pub struct Day2Solution {}
 
@@ -46,7 +49,7 @@ impl RPSResult {
     }
 }
 
-pub fn day02(input: &str) -> Result<f32> {
+pub fn day02(input: &str) -> Result<Solved> {
     solve_linear::<Day2Solution, _, _, _>(input)
 }
 
@@ -90,9 +93,15 @@ pub fn get_your_required_move(expected_result: RPSResult, them: RPS) -> RPS {
 impl SolutionLinear<Vec<(RPS, RPS)>, i32, i32> for Day2Solution {
     fn load(input: &str) -> Result<Vec<(RPS, RPS)>> {
         let mut moves: Vec<(RPS, RPS)> = Vec::new();
-        for line in input.lines() {
-            let items = line.split(" ").collect_vec();
-            moves.push((str_to_rps(items[0]).unwrap(), str_to_rps(items[1]).unwrap()));
+        for (i, line) in input.lines().enumerate() {
+            let bad_line = |message: String| InputError::at_line(input, i + 1, message);
+            let (them, you) = line
+                .split_once(' ')
+                .ok_or_else(|| bad_line("Expected two moves separated by a space".to_string()))?;
+            moves.push((
+                str_to_rps(them).map_err(|err| bad_line(err.to_string()))?,
+                str_to_rps(you).map_err(|err| bad_line(err.to_string()))?,
+            ));
         }
         Ok(moves)
     }
@@ -145,27 +154,13 @@ impl SolutionLinear<Vec<(RPS, RPS)>, i32, i32> for Day2Solution {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day2Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day2Solution;
+    (
         "A Y
 B X
 C Z
 ",
         15,
         12
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day2Solution::load(input).unwrap();
-        let p1 = Day2Solution::part1(&mut input).unwrap();
-        let p2 = Day2Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
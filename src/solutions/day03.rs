@@ -1,4 +1,5 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::solver_types::{solve_linear, SolutionLinear, Solved};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::collections::HashSet;
@@ -15,7 +16,7 @@ pub fn item_to_priority(input: char) -> Result<i32> {
     }
 }
 
-pub fn day03(input: &str) -> Result<f32> {
+pub fn day03(input: &str) -> Result<Solved> {
     solve_linear::<Day3Solution, _, _, _>(input)
 }
 
@@ -84,14 +85,8 @@ impl SolutionLinear<Vec<(Vec<char>, Vec<char>)>, i32, i32> for Day3Solution {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day3Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day3Solution;
+    (
         "vJrwpWtwJgWrhcsFMMfFFhFp
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg
@@ -101,13 +96,5 @@ CrZsJsPPZsGzwwsLwLmpwMDw
 ",
         157,
         70
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day3Solution::load(input).unwrap();
-        let p1 = Day3Solution::part1(&mut input).unwrap();
-        let p2 = Day3Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
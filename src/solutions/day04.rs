@@ -1,10 +1,13 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::{anyhow, Result};
-use itertools::Itertools;
+use crate::aoc_examples;
+use crate::utils::{
+    from_line::{parse_lines, FromLine},
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
+use anyhow::Result;
 
 pub struct Day4Solution {}
 
-pub fn day04(input: &str) -> Result<f32> {
+pub fn day04(input: &str) -> Result<Solved> {
     solve_linear::<Day4Solution, _, _, _>(input)
 }
 
@@ -31,34 +34,19 @@ impl Range {
     }
 }
 
+/// A line of the input, e.g. `2-4,6-8`
+#[derive(Debug, FromLine)]
+#[pattern(r"^(\d+)-(\d+),(\d+)-(\d+)$")]
+struct AssignmentPair(i32, i32, i32, i32);
+
 impl SolutionLinear<Vec<(Range, Range)>, i32, i32> for Day4Solution {
     fn load(input: &str) -> Result<Vec<(Range, Range)>> {
-        let mut range_pairs: Vec<(Range, Range)> = Vec::new();
-        for line in input.lines() {
-            let raw_pairs: Vec<Range> = line
-                .split(",")
-                .map(|x| {
-                    let parsed = x
-                        .split("-")
-                        .map(|y| y.parse::<i32>().unwrap())
-                        .collect_vec();
-
-                    if parsed.len() != 2 {
-                        panic!("Got other than 2 numbers from range split");
-                    }
-
-                    Range::new(parsed[0], parsed[1])
-                })
-                .collect_vec();
-
-            if raw_pairs.len() != 2 {
-                return Err(anyhow!("Got other than 2 ranges"));
-            }
-
-            range_pairs.push((raw_pairs[0], raw_pairs[1]));
-        }
-
-        Ok(range_pairs)
+        Ok(parse_lines::<AssignmentPair>(input)?
+            .into_iter()
+            .map(|AssignmentPair(lower_1, upper_1, lower_2, upper_2)| {
+                (Range::new(lower_1, upper_1), Range::new(lower_2, upper_2))
+            })
+            .collect())
     }
 
     fn part1(input: &mut Vec<(Range, Range)>) -> Result<i32> {
@@ -86,14 +74,8 @@ impl SolutionLinear<Vec<(Range, Range)>, i32, i32> for Day4Solution {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day4Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day4Solution;
+    (
         "2-4,6-8
 2-3,4-5
 5-7,7-9
@@ -102,13 +84,57 @@ mod tests {
 2-6,4-8",
         2,
         4
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day4Solution::load(input).unwrap();
-        let p1 = Day4Solution::part1(&mut input).unwrap();
-        let p2 = Day4Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
+    ),
+);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::Day4Solution;
+    use crate::utils::property::{check_solution, Oracle, Rng, DEFAULT_CASES};
+    use itertools::Itertools;
+
+    /// a range as a start and a length, so that shrinking can't produce a backwards range
+    type Assignment = (u32, u32);
+
+    fn sections((start, len): &Assignment) -> HashSet<u32> {
+        (*start..=start + len).collect()
+    }
+
+    const ORACLE: Oracle<Vec<(Assignment, Assignment)>, i32, i32> = Oracle {
+        generate: |rng: &mut Rng| {
+            rng.vec(0, 20, |rng| {
+                let assignment = |rng: &mut Rng| (rng.range(1, 20) as u32, rng.range(0, 8) as u32);
+                (assignment(rng), assignment(rng))
+            })
+        },
+        valid: |_| true,
+        to_input: |pairs| {
+            pairs
+                .iter()
+                .map(|((a, a_len), (b, b_len))| format!("{}-{},{}-{}", a, a + a_len, b, b + b_len))
+                .join("\n")
+        },
+        part1: |pairs| {
+            pairs
+                .iter()
+                .filter(|(a, b)| {
+                    let (a, b) = (sections(a), sections(b));
+                    a.is_subset(&b) || b.is_subset(&a)
+                })
+                .count() as i32
+        },
+        part2: |pairs| {
+            pairs
+                .iter()
+                .filter(|(a, b)| !sections(a).is_disjoint(&sections(b)))
+                .count() as i32
+        },
+    };
+
+    #[test]
+    fn matches_reference() {
+        check_solution::<Day4Solution, _, _, _, _>(DEFAULT_CASES, 4, &ORACLE);
     }
 }
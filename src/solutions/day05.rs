@@ -1,88 +1,110 @@
 use std::vec;
 
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::{
+    from_line::{parse_lines, FromLine},
+    input_error::InputError,
+    parser::{
+        any_char, bracketed, int, line_ending, literal, parse_all, separated, separated1,
+        take_while1, Parser,
+    },
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
 use anyhow::Result;
 use itertools::Itertools;
-use regex::Regex;
-use transpose::transpose;
 
 pub struct Day5Solution {}
 
-pub fn day05(input: &str) -> Result<f32> {
+/// The drawing of the stacks: rows of crates like `[Z] [M] [P]`, where a gap is a missing crate,
+/// followed by the row of stack numbers
+fn drawing<'a>() -> impl Parser<'a, (Vec<Vec<Option<char>>>, Vec<usize>)> {
+    let item = bracketed("[", any_char(), "]")
+        .map(Some)
+        .or(literal("   ").map(|_| None));
+    let row = separated1("a crate", item, literal(" "));
+    let spaces = || take_while1("a space", |c| c == ' ');
+    let stack_numbers = spaces().and(separated1("a stack number", int::<usize>(), spaces()));
+
+    // not `lines`, since the rows end where a line isn't a row of crates
+    separated(row, line_ending())
+        .skip(line_ending())
+        .and(stack_numbers)
+        .map(|(rows, (_, stack_numbers))| (rows, stack_numbers))
+}
+
+#[derive(Debug, Clone, FromLine)]
+#[pattern(r"^move (\d+) from (\d+) to (\d+)$")]
+struct Instruction {
+    count: i32,
+    from: i32,
+    to: i32,
+}
+
+pub fn day05(input: &str) -> Result<Solved> {
     solve_linear::<Day5Solution, _, _, _>(input)
 }
 
-impl SolutionLinear<(Vec<Vec<char>>, Vec<(i32, i32, i32)>), String, String> for Day5Solution {
-    fn load(input: &str) -> Result<(Vec<Vec<char>>, Vec<(i32, i32, i32)>)> {
+impl SolutionLinear<(Vec<Vec<char>>, Vec<Instruction>), String, String> for Day5Solution {
+    fn load(input: &str) -> Result<(Vec<Vec<char>>, Vec<Instruction>)> {
         let lines: Vec<&str> = input.lines().collect();
         let parts = lines.split(|x| x.to_string().eq("")).collect_vec();
 
-        // Initial parse
-        let mut stacks: Vec<Vec<char>> = Vec::new();
-        let lines_num = parts[0].len();
-        for line in parts[0] {
-            let stack_chars = (**line).chars().collect_vec();
-            let items = stack_chars.chunks(4).collect_vec();
-            let mut stack: Vec<char> = Vec::new();
-
-            for item in items {
-                stack.push(item[1]);
+        let drawn = parts[0].join("\n");
+        let (rows, stack_numbers) = parse_all(&drawing(), &drawn)?;
+
+        let mut output_stacks: Vec<Vec<char>> = vec![Vec::new(); stack_numbers.len()];
+        for (i, row) in rows.iter().enumerate().rev() {
+            if row.len() > output_stacks.len() {
+                return Err(InputError::at_line(
+                    input,
+                    i + 1,
+                    format!(
+                        "Found a row of {} crates, but there are only {} stacks",
+                        row.len(),
+                        output_stacks.len()
+                    ),
+                )
+                .into());
             }
-
-            stacks.push(stack);
-        }
-
-        let mut flat_stacks: Vec<char> = Vec::new();
-        for stack in stacks {
-            for item in stack {
-                flat_stacks.push(item);
+            for (stack, item) in output_stacks.iter_mut().zip(row) {
+                if let Some(item) = item {
+                    stack.push(*item);
+                }
             }
         }
 
-        let lines_width = flat_stacks.len() / lines_num;
-        let mut transposed_flat_stacks: Vec<char> = vec!['_'; flat_stacks.len()];
-
-        transpose(
-            flat_stacks.as_slice(),
-            transposed_flat_stacks.as_mut_slice(),
-            lines_width,
-            lines_num,
-        );
-
-        let transposed_stacks = transposed_flat_stacks.chunks(lines_num).collect_vec();
-
-        let mut output_stacks: Vec<Vec<char>> = Vec::new();
-        for tranposed_stack in transposed_stacks {
-            let mut vec_stack = tranposed_stack.to_vec();
-            vec_stack.reverse();
-            while vec_stack[vec_stack.len() - 1] == ' ' {
-                vec_stack.pop();
+        let instruction_lines = parts.get(1).ok_or_else(|| {
+            InputError::at_line(
+                input,
+                parts[0].len(),
+                "Expected a blank line between the drawing and the instructions",
+            )
+        })?;
+        let first_instruction = parts[0].len() + 1;
+        let instructions = parse_lines::<Instruction>(&instruction_lines.join("\n"))
+            .map_err(|err| err.shift_lines(first_instruction))?;
+
+        for (i, instruction) in instructions.iter().enumerate() {
+            let is_stack = |stack: i32| stack >= 1 && stack as usize <= output_stacks.len();
+            if !is_stack(instruction.from) || !is_stack(instruction.to) {
+                return Err(InputError::at_line(
+                    input,
+                    first_instruction + i + 1,
+                    format!("There are only {} stacks", output_stacks.len()),
+                )
+                .into());
             }
-            output_stacks.push(vec_stack);
-        }
-
-        let mut instructions: Vec<(i32, i32, i32)> = Vec::new();
-
-        let instruction_regex = Regex::new(r"^move ([0-9]+) from ([0-9]+) to ([0-9]+)$").unwrap();
-        for line in parts[1] {
-            let result = instruction_regex.captures(line).unwrap();
-
-            let count = &result[1].parse::<i32>().unwrap();
-            let start = &result[2].parse::<i32>().unwrap();
-            let end = &result[3].parse::<i32>().unwrap();
-
-            instructions.push((*count, *start, *end));
         }
 
         Ok((output_stacks, instructions))
     }
 
-    fn part1(input: &mut (Vec<Vec<char>>, Vec<(i32, i32, i32)>)) -> Result<String> {
+    fn part1(input: &mut (Vec<Vec<char>>, Vec<Instruction>)) -> Result<String> {
         let (mut stacks, instructions) = input.clone();
 
-        for (count, start, end) in instructions {
-            let start_idx: usize = (start - 1).try_into().unwrap();
-            let end_idx: usize = (end - 1).try_into().unwrap();
+        for Instruction { count, from, to } in instructions {
+            let start_idx: usize = (from - 1).try_into().unwrap();
+            let end_idx: usize = (to - 1).try_into().unwrap();
             for _ in 0..count {
                 let item = stacks[start_idx].pop().unwrap();
                 stacks[end_idx].push(item);
@@ -99,14 +121,14 @@ impl SolutionLinear<(Vec<Vec<char>>, Vec<(i32, i32, i32)>), String, String> for
     }
 
     fn part2(
-        input: &mut (Vec<Vec<char>>, Vec<(i32, i32, i32)>),
+        input: &mut (Vec<Vec<char>>, Vec<Instruction>),
         _part_1_solution: String,
     ) -> Result<String> {
         let (mut stacks, instructions) = input.clone();
 
-        for (count, start, end) in instructions {
-            let start_idx: usize = (start - 1).try_into().unwrap();
-            let end_idx: usize = (end - 1).try_into().unwrap();
+        for Instruction { count, from, to } in instructions {
+            let start_idx: usize = (from - 1).try_into().unwrap();
+            let end_idx: usize = (to - 1).try_into().unwrap();
             let mut items: Vec<char> = Vec::new();
             for _ in 0..count {
                 let item = stacks[start_idx].pop().unwrap();
@@ -128,19 +150,10 @@ impl SolutionLinear<(Vec<Vec<char>>, Vec<(i32, i32, i32)>), String, String> for
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day5Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
-        "    [D]    
-[N] [C]    
-[Z] [M] [P]
- 1   2   3 
-
+aoc_examples!(linear Day5Solution;
+    (
+        // the crate diagram is padded with trailing spaces, so spell out the line breaks
+        "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n\
 move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
@@ -148,18 +161,5 @@ move 1 from 1 to 2
 ",
         "CMZ",
         "MCD"
-    )]
-    fn validate_linear(
-        #[case] input: &str,
-        #[case] expected_1: String,
-        #[case] expected_2: String,
-    ) {
-        let mut input = Day5Solution::load(input).unwrap();
-        let p1 = Day5Solution::part1(&mut input).unwrap();
-        let p1_clone = p1.clone();
-        let p2 = Day5Solution::part2(&mut input, p1_clone).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
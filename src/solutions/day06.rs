@@ -1,23 +1,56 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::solver_types::{solve_linear, SolutionLinear, Solved};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Day6Solution {}
 
-pub fn day06(input: &str) -> Result<f32> {
+pub fn day06(input: &str) -> Result<Solved> {
     solve_linear::<Day6Solution, _, _, _>(input)
 }
 
+/// Same as `Day6Solution`, but slides a window along the input instead of building a set at every position
+pub struct Day6FastSolution {}
+
+pub fn day06_fast(input: &str) -> Result<Solved> {
+    solve_linear::<Day6FastSolution, _, _, _>(input)
+}
+
 fn generic_solution(input: &mut Vec<char>, len_of_set: usize) -> Result<i32> {
-    for index in 0..(input.len() - len_of_set) {
-        let set: HashSet<char> = input[index..index + len_of_set]
-            .into_iter()
-            .copied()
-            .collect();
+    // no windows at all if the input is shorter than one
+    for (index, window) in input.windows(len_of_set).enumerate() {
+        let set: HashSet<char> = window.iter().copied().collect();
         if set.len() == len_of_set {
-            let result: i32 = (index + len_of_set).try_into().unwrap();
+            let result: i32 = (index + len_of_set).try_into()?;
+            return Ok(result);
+        }
+    }
+
+    Err(anyhow!("Unique set not found"))
+}
+
+/// Keep count of the characters in the window, so each step only has to account for the character
+/// entering the window and the one leaving it
+fn sliding_window_solution(input: &[char], len_of_set: usize) -> Result<i32> {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+
+    for (index, item) in input.iter().enumerate() {
+        *counts.entry(*item).or_insert(0) += 1;
+
+        if index >= len_of_set {
+            let leaving = input[index - len_of_set];
+            if let Some(count) = counts.get_mut(&leaving) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(&leaving);
+                }
+            }
+        }
+
+        if counts.len() == len_of_set {
+            let result: i32 = (index + 1).try_into()?;
             return Ok(result);
         }
     }
@@ -49,24 +82,46 @@ impl SolutionLinear<Vec<char>, i32, i32> for Day6Solution {
     }
 }
 
+impl SolutionLinear<Vec<char>, i32, i32> for Day6FastSolution {
+    fn load(input: &str) -> Result<Vec<char>> {
+        Day6Solution::load(input)
+    }
+
+    fn part1(input: &mut Vec<char>) -> Result<i32> {
+        sliding_window_solution(input, 4)
+    }
+
+    fn part2(input: &mut Vec<char>, _part_1_solution: i32) -> Result<i32> {
+        sliding_window_solution(input, 14)
+    }
+}
+
+aoc_examples!(linear Day6Solution;
+    ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+    ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+    ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+    ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+    ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+);
+
 #[cfg(test)]
 mod tests {
-    use super::Day6Solution;
-    use crate::utils::solver_types::SolutionLinear;
     use rstest::rstest;
 
+    use super::{generic_solution, sliding_window_solution};
+
     #[rstest]
-    #[case("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19)]
-    #[case("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23)]
-    #[case("nppdvjthqldpwncqszvftbrmjlhg", 6, 23)]
-    #[case("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29)]
-    #[case("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26)]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day6Solution::load(input).unwrap();
-        let p1 = Day6Solution::part1(&mut input).unwrap();
-        let p2 = Day6Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
+    #[case::only_the_last_window("aaaabcd", 4, Some(7))]
+    #[case::the_whole_input("abcd", 4, Some(4))]
+    #[case::shorter_than_a_window("abcdefg", 14, None)]
+    #[case::no_unique_window("abab", 4, None)]
+    fn naive_and_fast_agree(
+        #[case] input: &str,
+        #[case] len_of_set: usize,
+        #[case] expected: Option<i32>,
+    ) {
+        let mut chars = input.chars().collect::<Vec<_>>();
+        assert_eq!(expected, generic_solution(&mut chars, len_of_set).ok());
+        assert_eq!(expected, sliding_window_solution(&chars, len_of_set).ok());
     }
 }
@@ -1,4 +1,10 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::{
+    checked,
+    input_error::InputError,
+    parser::{int, lines, literal, parse_all, take_while1, Input, ParseResult, Parser},
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::collections::HashMap;
@@ -6,10 +12,36 @@ use std::collections::HashSet;
 
 pub struct Day7Solution {}
 
-pub fn day07(input: &str) -> Result<f32> {
+pub fn day07(input: &str) -> Result<Solved> {
     solve_linear::<Day7Solution, _, _, _>(input)
 }
 
+/// A line of the shell transcript
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TerminalLine<'a> {
+    Cd(&'a str),
+    Ls,
+    Dir(&'a str),
+    File(i32, &'a str),
+}
+
+fn terminal_line(input: Input) -> ParseResult<TerminalLine> {
+    let name = || take_while1("a name", |c| c != '\n' && c != '\r');
+
+    literal("$ cd ")
+        .and(name())
+        .map(|(_, folder)| TerminalLine::Cd(folder))
+        .or(literal("$ ls").map(|_| TerminalLine::Ls))
+        .or(literal("dir ")
+            .and(name())
+            .map(|(_, name)| TerminalLine::Dir(name)))
+        .or(int::<i32>()
+            .skip(literal(" "))
+            .and(name())
+            .map(|(size, name)| TerminalLine::File(size, name)))
+        .parse(input)
+}
+
 #[derive(Clone, Debug)]
 struct Item {
     size: i32,
@@ -17,7 +49,8 @@ struct Item {
     is_file: bool,
 }
 
-fn backfill_sizes(structure: &mut HashMap<String, Item>, curr_path: Option<String>) {
+/// Fill in the sizes of folders from the files in them. Errors if a size doesn't fit in an `i32`
+fn backfill_sizes(structure: &mut HashMap<String, Item>, curr_path: Option<String>) -> Result<()> {
     let mut path: Vec<String> = Vec::new();
     match curr_path {
         Some(str) => path.push(str),
@@ -35,22 +68,26 @@ fn backfill_sizes(structure: &mut HashMap<String, Item>, curr_path: Option<Strin
         let test_path = path.join("/");
         let test_item = structure.get(test_path.as_str()).cloned();
         if !test_item.expect(test_path.as_str()).is_file {
-            backfill_sizes(structure, Some(test_path.clone()));
+            backfill_sizes(structure, Some(test_path.clone()))?;
         }
 
-        size += structure.get(test_path.as_str()).unwrap().size;
+        size = structure
+            .get(test_path.as_str())
+            .and_then(|item| item.size.checked_add(size))
+            .ok_or_else(|| anyhow!("The size of '/{}' is too large", str_path))?;
         path.pop();
     }
 
     // println!("Size {size} at path {str_path}");
     structure.get_mut(str_path.as_str()).unwrap().size = size;
+    Ok(())
 }
 
 fn count_sizes_that_are_at_most(
     structure: &mut HashMap<String, Item>,
     upper_limit: i32,
     curr_path: Option<String>,
-) -> i32 {
+) -> Result<i32> {
     let mut path: Vec<String> = Vec::new();
     match curr_path {
         Some(str) => path.push(str),
@@ -65,7 +102,7 @@ fn count_sizes_that_are_at_most(
     let curr_size = curr_item.expect(str_path.as_str()).size;
     // println!("Testing item {str_path} with size {curr_size}");
     if curr_size <= upper_limit {
-        size += curr_size;
+        size = checked::add(size, curr_size, "the total of small folders")?;
     }
 
     for item in items {
@@ -74,18 +111,21 @@ fn count_sizes_that_are_at_most(
         let test_path = path.join("/");
         let test_item = structure.get(test_path.as_str()).cloned();
         if !test_item.clone().expect(test_path.as_str()).is_file {
-            size += count_sizes_that_are_at_most(structure, upper_limit, Some(test_path.clone()));
+            let folder_sizes =
+                count_sizes_that_are_at_most(structure, upper_limit, Some(test_path.clone()))?;
+            size = checked::add(size, folder_sizes, "the total of small folders")?;
         }
         path.pop();
     }
-    size
+    Ok(size)
 }
 
 impl SolutionLinear<HashMap<String, Item>, i32, i32> for Day7Solution {
     fn load(input: &str) -> Result<HashMap<String, Item>> {
-        let mut lines = input.lines().collect_vec();
-        if lines.remove(0) != "$ cd /" {
-            return Err(anyhow!("Expected cd into root at line 0"));
+        let transcript = parse_all(&lines(terminal_line), input)?;
+        let mut transcript = transcript.into_iter();
+        if transcript.next() != Some(TerminalLine::Cd("/")) {
+            return Err(InputError::at_line(input, 1, "Expected cd into root").into());
         }
 
         let mut path: Vec<String> = Vec::new();
@@ -102,131 +142,79 @@ impl SolutionLinear<HashMap<String, Item>, i32, i32> for Day7Solution {
 
         let mut expecting_files = false;
 
-        for line in lines {
-            let parts = line.split(" ").collect_vec();
+        // the first line was the cd into root
+        for (i, line) in transcript.enumerate() {
             str_path = path.join("/").to_string();
-            match parts[0] {
-                "$" => {
+            let (name, size, is_file) = match line {
+                TerminalLine::Cd("/") => {
+                    expecting_files = false;
+                    path.clear();
+                    continue;
+                }
+                TerminalLine::Cd("..") => {
+                    expecting_files = false;
+                    path.pop();
+                    continue;
+                }
+                TerminalLine::Cd(folder_name) => {
                     expecting_files = false;
-                    match parts[1] {
-                        "cd" => {
-                            match parts[2] {
-                                "/" => {
-                                    // println!("Cd root");
-                                    path.clear();
-                                }
-                                ".." => {
-                                    // println!("Cd up");
-                                    path.pop();
-                                }
-                                folder_name => {
-                                    let curr_folder = structure.get_mut(str_path.as_str());
-                                    let not_found;
-                                    match curr_folder {
-                                        Some(item) => {
-                                            not_found = item.items.insert(folder_name.to_string());
-                                        }
-                                        None => {
-                                            panic!()
-                                        }
-                                    }
-                                    path.push(folder_name.to_string());
-
-                                    if not_found {
-                                        let folder = Item {
-                                            size: 0,
-                                            items: HashSet::new(),
-                                            is_file: false,
-                                        };
-                                        structure.insert(path.join("/").to_string(), folder);
-                                    }
-
-                                    // println!("cd into {folder_name} from {str_path}");
-                                }
-                            }
-                        }
-                        "ls" => {
-                            expecting_files = true;
-                        }
-                        _ => return Err(anyhow!("Unknown symbol")),
-                    }
+                    (folder_name, 0, false)
                 }
-                "dir" => {
-                    if expecting_files {
-                        let name = parts[1].to_string();
-
-                        let curr_folder = structure.get_mut(str_path.as_str());
-                        let not_found;
-                        match curr_folder {
-                            Some(item) => {
-                                not_found = item.items.insert(name.clone());
-                            }
-                            None => {
-                                panic!()
-                            }
-                        }
-
-                        if not_found {
-                            path.push(name.to_string());
-                            let folder = Item {
-                                size: 0,
-                                items: HashSet::new(),
-                                is_file: false,
-                            };
-
-                            structure.insert(path.join("/"), folder);
-                            path.pop();
-                        }
-
-                        // println!("Create dir {name} at {str_path}");
-                    } else {
-                        return Err(anyhow!("Unknown symbol"));
-                    }
+                TerminalLine::Ls => {
+                    expecting_files = true;
+                    continue;
                 }
-                size => {
-                    if expecting_files {
-                        let num_size = size.parse::<i32>().unwrap();
-                        let name = parts[1].to_string();
-
-                        let curr_folder = structure.get_mut(str_path.as_str());
-                        let not_found;
-                        match curr_folder {
-                            Some(item) => {
-                                not_found = item.items.insert(name.clone());
-                            }
-                            None => {
-                                panic!()
-                            }
-                        }
-
-                        if not_found {
-                            path.push(name.to_string());
-                            let file = Item {
-                                size: num_size,
-                                items: HashSet::new(),
-                                is_file: true,
-                            };
-
-                            structure.insert(path.join("/"), file);
-                            path.pop();
-                        }
-
-                        // println!("Create file {name} size {num_size} at {str_path}");
-                    } else {
-                        return Err(anyhow!("Unknown symbol"));
-                    }
+                TerminalLine::Dir(name) if expecting_files => (name, 0, false),
+                TerminalLine::File(size, name) if expecting_files => (name, size, true),
+                _ => {
+                    return Err(InputError::at_line(
+                        input,
+                        i + 2,
+                        "Unexpected listing outside of ls",
+                    )
+                    .into())
+                }
+            };
+
+            let curr_folder = structure.get_mut(str_path.as_str());
+            let not_found;
+            match curr_folder {
+                Some(item) => {
+                    not_found = item.items.insert(name.to_string());
+                }
+                None => {
+                    return Err(InputError::at_line(
+                        input,
+                        i + 2,
+                        format!("Folder '/{}' was never listed", str_path),
+                    )
+                    .into())
                 }
             }
-        }
 
-        backfill_sizes(&mut structure, None);
+            path.push(name.to_string());
+            if not_found {
+                let item = Item {
+                    size,
+                    items: HashSet::new(),
+                    is_file,
+                };
+                structure.insert(path.join("/"), item);
+            }
+
+            // cd stays in the folder, listings don't
+            if !matches!(line, TerminalLine::Cd(_)) {
+                path.pop();
+            }
+        }
+        backfill_sizes(&mut structure, None)?;
 
         Ok(structure)
     }
 
     fn part1(input: &mut HashMap<String, Item>) -> Result<i32> {
         // println!("{input:?}");
-        let result = count_sizes_that_are_at_most(input, 100000, None);
+        let result = count_sizes_that_are_at_most(input, 100000, None)?;
         // println!("P1 result {result}");
         Ok(result)
     }
@@ -235,7 +223,8 @@ impl SolutionLinear<HashMap<String, Item>, i32, i32> for Day7Solution {
         let root_size = input.get("").cloned().unwrap().size;
         let total_size = 70000000;
         let target_size = 30000000;
-        let need_size = target_size - (total_size - root_size);
+        let free_size = checked::sub(total_size, root_size, "the free space")?;
+        let need_size = checked::sub(target_size, free_size, "the space to free up")?;
 
         let mut sizes = input
             .values()
@@ -256,14 +245,8 @@ impl SolutionLinear<HashMap<String, Item>, i32, i32> for Day7Solution {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day7Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day7Solution;
+    (
         "$ cd /
 $ ls
 dir a
@@ -289,13 +272,5 @@ $ ls
 7214296 k",
         95437,
         24933642
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day7Solution::load(input).unwrap();
-        let p1 = Day7Solution::part1(&mut input).unwrap();
-        let p2 = Day7Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
@@ -1,187 +1,82 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::{
+    direction::Dir4,
+    grid::Grid,
+    input_error::{InputError, Span},
+    point::Pt,
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
 use anyhow::{anyhow, Result};
-use itertools::Itertools;
 
 pub struct Day8Solution {}
 
-pub fn day08(input: &str) -> Result<f32> {
+pub fn day08(input: &str) -> Result<Solved> {
     solve_linear::<Day8Solution, _, _, _>(input)
 }
 
-impl SolutionLinear<Vec<Vec<i32>>, i32, i32> for Day8Solution {
-    fn load(input: &str) -> Result<Vec<Vec<i32>>> {
-        //https://stackoverflow.com/questions/43983414/how-to-convert-a-rust-char-to-an-integer-so-that-1-becomes-1#43985962
-        //https://stackoverflow.com/questions/67829405/how-to-convert-a-u32-constant-to-i32-constant-without-unsafe-in-rust
-        let forest = input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|x| x.to_digit(10).expect("issue converting to int") as i32)
-                    .collect_vec()
+impl SolutionLinear<Grid<i32, 2>, i32, i32> for Day8Solution {
+    fn load(input: &str) -> Result<Grid<i32, 2>> {
+        let grid = Grid::try_from_text(input, |Pt([x, y]), c| {
+            c.to_digit(10).map(|height| height as i32).ok_or_else(|| {
+                InputError::new(
+                    Span {
+                        line: y as usize + 1,
+                        column: x as usize + 1,
+                        len: 1,
+                    },
+                    format!("Expected a tree height, found '{}'", c),
+                )
             })
-            .collect_vec();
-        Ok(forest)
-    }
-
-    fn part1(input: &mut Vec<Vec<i32>>) -> Result<i32> {
-        let height = input.len();
-        let width = input[0].len();
-
-        let mut visible_map = vec![vec![0; width]; height];
-
-        // Fill initial
-        for i in 0..width {
-            visible_map[0][i] = 1;
-            visible_map[height - 1][i] = 1;
-        }
-
-        for i in 1..height - 1 {
-            visible_map[i][0] = 1;
-            visible_map[i][width - 1] = 1;
-        }
-
-        // Scan down
-        let mut height_map = vec![vec![0; width]; height];
-        for i in 0..width {
-            height_map[0][i] = input[0][i];
-        }
-
-        for i in 1..height {
-            for j in 0..width {
-                if input[i][j] > height_map[i - 1][j] {
-                    height_map[i][j] = input[i][j];
-                    visible_map[i][j] = 1;
-                } else {
-                    height_map[i][j] = height_map[i - 1][j];
-                }
-            }
-        }
-
-        // Scan up
-        let mut height_map = vec![vec![0; width]; height];
-        for i in 0..width {
-            height_map[height - 1][i] = input[height - 1][i];
-        }
-
-        for pos_i in 1..height {
-            let i = height - (pos_i + 1);
-            for j in 0..width {
-                if input[i][j] > height_map[i + 1][j] {
-                    height_map[i][j] = input[i][j];
-                    visible_map[i][j] = 1;
-                } else {
-                    height_map[i][j] = height_map[i + 1][j];
-                }
-            }
-        }
-
-        // Scan right
-        let mut height_map = vec![vec![0; width]; height];
-        for i in 0..height {
-            height_map[i][0] = input[i][0];
-        }
-
-        for j in 1..width {
-            for i in 0..height {
-                if input[i][j] > height_map[i][j - 1] {
-                    height_map[i][j] = input[i][j];
-                    visible_map[i][j] = 1;
-                } else {
-                    height_map[i][j] = height_map[i][j - 1];
-                }
-            }
-        }
-
-        // Scan left
-        let mut height_map = vec![vec![0; width]; height];
-        for i in 0..height {
-            height_map[i][width - 1] = input[i][width - 1];
+        })?;
+
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        if let Some(i) = input.lines().position(|line| line.chars().count() != width) {
+            return Err(InputError::at_line(
+                input,
+                i + 1,
+                format!("Expected a row of {} trees", width),
+            )
+            .into());
         }
 
-        for pos_j in 1..width {
-            let j = width - (pos_j + 1);
-            for i in 0..height {
-                if input[i][j] > height_map[i][j + 1] {
-                    height_map[i][j] = input[i][j];
-                    visible_map[i][j] = 1;
-                } else {
-                    height_map[i][j] = height_map[i][j + 1];
-                }
-            }
-        }
+        Ok(grid)
+    }
 
-        let result = visible_map
+    fn part1(input: &mut Grid<i32, 2>) -> Result<i32> {
+        let visible = input
+            .grid
             .iter()
-            .map(|x| x.iter().sum::<i32>())
-            .sum::<i32>();
-        Ok(result)
+            .filter(|(pt, height)| {
+                input.visible_from_outside(pt, Dir4::ALL.map(Dir4::offset), |other| {
+                    other >= **height
+                })
+            })
+            .count();
+        Ok(visible as i32)
     }
 
-    fn part2(input: &mut Vec<Vec<i32>>, _part_1_solution: i32) -> Result<i32> {
-        let height = input.len();
-        let width = input[0].len();
-
-        let mut max_result = 0;
-
-        for i in 0..height {
-            for j in 0..width {
-                let test_num = input[i][j];
-
-                let mut down_count = 0;
-                for k in (i + 1)..height {
-                    down_count += 1;
-                    if input[k][j] < test_num {
-                    } else {
-                        break;
-                    }
-                }
-
-                let mut up_count = 0;
-                for k in 0..i {
-                    up_count += 1;
-                    if input[i - k - 1][j] < test_num {
-                    } else {
-                        break;
-                    }
-                }
-
-                let mut right_count = 0;
-                for k in (j + 1)..width {
-                    right_count += 1;
-                    if input[i][k] < test_num {
-                    } else {
-                        break;
-                    }
-                }
-
-                let mut left_count = 0;
-                for k in 0..j {
-                    left_count += 1;
-                    if input[i][j - k - 1] < test_num {
-                    } else {
-                        break;
-                    }
-                }
-
-                let result = up_count * down_count * left_count * right_count;
-
-                if result > max_result {
-                    max_result = result;
-                }
-            }
-        }
+    fn part2(input: &mut Grid<i32, 2>, _part_1_solution: i32) -> Result<i32> {
+        let max_result = input
+            .grid
+            .iter()
+            .map(|(pt, height)| {
+                Dir4::ALL
+                    .into_iter()
+                    .map(|dir| {
+                        input
+                            .line_of_sight(pt, dir.offset(), |other| other >= *height)
+                            .count() as i32
+                    })
+                    .product()
+            })
+            .max()
+            .unwrap_or(0);
         Ok(max_result)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day8Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day8Solution;
+    (
         "30373
 25512
 65332
@@ -189,13 +84,5 @@ mod tests {
 35390",
         21,
         8
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day8Solution::load(input).unwrap();
-        let p1 = Day8Solution::part1(&mut input).unwrap();
-        let p2 = Day8Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
@@ -1,149 +1,184 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::{
+    direction::Dir4,
+    from_line::{parse_lines, FromLine},
+    point::Pt,
+    simulation::{run_to_completion, Simulation},
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::collections::HashSet;
 
 pub struct Day9Solution {}
 
-pub fn day09(input: &str) -> Result<f32> {
+pub fn day09(input: &str) -> Result<Solved> {
     solve_linear::<Day9Solution, _, _, _>(input)
 }
 
-#[derive(Debug, Clone)]
-pub enum Direction {
-    Up(usize),
-    Down(usize),
-    Left(usize),
-    Right(usize),
+/// A line of the input, e.g. `R 4`
+#[derive(Debug, Clone, FromLine)]
+#[pattern(r"^(\S+) (\d+)$")]
+pub struct Motion {
+    dir: Dir4,
+    steps: usize,
 }
 
-impl Direction {
-    fn make(input: &str, num: usize) -> Direction {
-        match input {
-            "U" => Direction::Up(num),
-            "D" => Direction::Down(num),
-            "L" => Direction::Left(num),
-            "R" => Direction::Right(num),
-            _ => panic!(),
+fn propegate_rope(rope: &mut Vec<Pt<2>>) {
+    for i in 1..rope.len() {
+        // a knot that isn't touching the one in front moves one square towards it, diagonally if needed
+        let gap = rope[i - 1] - rope[i];
+        if gap.chebyshev() > 1 {
+            rope[i] += gap.signum();
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct Point {
-    x: i32,
-    y: i32,
+/// Moves the head of the rope one square per step, dragging the rest of the rope behind it
+pub struct RopeSimulation {
+    moves: Vec<Motion>,
+    /// the move currently being carried out
+    move_idx: usize,
+    /// how many squares of the current move have been carried out
+    moved: usize,
+    rope: Vec<Pt<2>>,
+    visited: HashSet<Pt<2>>,
 }
 
-fn propegate_rope(rope: &mut Vec<Point>) {
-    for i in 1..rope.len() {
-        if (rope[i].y - rope[i - 1].y).pow(2) + (rope[i].x - rope[i - 1].x).pow(2) > 2 {
-            if rope[i].y > rope[i - 1].y {
-                rope[i].y -= 1;
-            } else if rope[i].y < rope[i - 1].y {
-                rope[i].y += 1;
-            }
+impl RopeSimulation {
+    fn new(moves: Vec<Motion>, rope_length: usize) -> Self {
+        let rope = vec![Pt::default(); rope_length];
+        let mut visited = HashSet::new();
+        visited.insert(rope[rope.len() - 1]);
+
+        let mut sim = Self {
+            moves,
+            move_idx: 0,
+            moved: 0,
+            rope,
+            visited,
+        };
+        sim.skip_empty_moves();
+        sim
+    }
 
-            if rope[i].x > rope[i - 1].x {
-                rope[i].x -= 1;
-            } else if rope[i].x < rope[i - 1].x {
-                rope[i].x += 1;
-            }
+    fn skip_empty_moves(&mut self) {
+        while self.move_idx < self.moves.len() && self.moves[self.move_idx].steps == 0 {
+            self.move_idx += 1;
         }
     }
-}
 
-fn simulate(input: Vec<Direction>, rope_length: usize) -> i32 {
-    let mut rope = vec![Point { x: 0, y: 0 }; rope_length];
+    fn tail(&self) -> &Pt<2> {
+        &self.rope[self.rope.len() - 1]
+    }
+}
 
-    let mut set: HashSet<Point> = HashSet::new();
-    set.insert(rope[rope.len() - 1].clone());
+impl Simulation for RopeSimulation {
+    fn step(&mut self) -> Result<()> {
+        let Motion { dir, steps } = self.moves[self.move_idx];
 
-    for mov in input {
-        match mov {
-            Direction::Up(num) => {
-                let count = num;
+        self.rope[0] += dir.offset();
 
-                for _ in 0..count {
-                    rope[0].y -= 1;
+        propegate_rope(&mut self.rope);
 
-                    propegate_rope(&mut rope);
+        self.visited.insert(*self.tail());
 
-                    set.insert(rope[rope.len() - 1].clone());
-                }
-            }
-            Direction::Down(num) => {
-                let count = num;
+        self.moved += 1;
+        if self.moved >= steps {
+            self.move_idx += 1;
+            self.moved = 0;
+            self.skip_empty_moves();
+        }
 
-                for _ in 0..count {
-                    rope[0].y += 1;
+        Ok(())
+    }
 
-                    propegate_rope(&mut rope);
+    fn is_done(&self) -> bool {
+        self.move_idx >= self.moves.len()
+    }
 
-                    set.insert(rope[rope.len() - 1].clone());
-                }
+    fn render(&self) -> String {
+        let start = Pt::default();
+        let points = self
+            .visited
+            .iter()
+            .chain(self.rope.iter())
+            .chain([&start])
+            .collect_vec();
+
+        let min = points.iter().fold(start, |min, p| min.component_min(**p));
+        let max = points.iter().fold(start, |max, p| max.component_max(**p));
+
+        let mut output = String::new();
+        for y in min.0[1]..max.0[1] + 1 {
+            for x in min.0[0]..max.0[0] + 1 {
+                let point = Pt([x, y]);
+                let knot = self.rope.iter().position(|knot| *knot == point);
+                let c = match knot {
+                    Some(0) => 'H',
+                    Some(_) if self.rope.len() == 2 => 'T',
+                    Some(i) => char::from_digit(i as u32, 36).unwrap_or('*'),
+                    None if point == start => 's',
+                    None if self.visited.contains(&point) => '#',
+                    None => '.',
+                };
+                output.push(c);
             }
-            Direction::Left(num) => {
-                let count = num;
+            output.push('\n');
+        }
 
-                for _ in 0..count {
-                    rope[0].x -= 1;
+        output
+    }
 
-                    propegate_rope(&mut rope);
+    fn watches(&self) -> Vec<(String, i64)> {
+        let head = &self.rope[0];
+        let tail = self.tail();
+        vec![
+            ("move".to_string(), self.move_idx as i64),
+            ("head_x".to_string(), head.0[0] as i64),
+            ("head_y".to_string(), head.0[1] as i64),
+            ("tail_x".to_string(), tail.0[0] as i64),
+            ("tail_y".to_string(), tail.0[1] as i64),
+            ("visited".to_string(), self.visited.len() as i64),
+        ]
+    }
+}
 
-                    set.insert(rope[rope.len() - 1].clone());
-                }
-            }
-            Direction::Right(num) => {
-                let count = num;
+fn simulate(input: Vec<Motion>, rope_length: usize) -> Result<i32> {
+    let mut sim = RopeSimulation::new(input, rope_length);
+    run_to_completion(&mut sim)?;
 
-                for _ in 0..count {
-                    rope[0].x += 1;
+    let result: i32 = sim.visited.len().try_into()?;
 
-                    propegate_rope(&mut rope);
+    Ok(result)
+}
 
-                    set.insert(rope[rope.len() - 1].clone());
-                }
-            }
-        }
+/// Build the rope simulation for the given part, for stepping through in the debugger
+pub fn simulation(input: &str, part: usize) -> Result<Box<dyn Simulation>> {
+    let moves = Day9Solution::load(input)?;
+    match part {
+        1 => Ok(Box::new(RopeSimulation::new(moves, 2))),
+        2 => Ok(Box::new(RopeSimulation::new(moves, 10))),
+        _ => Err(anyhow!("Day 9 has no part {}", part)),
     }
-
-    let result: i32 = set.len().try_into().unwrap();
-
-    result
 }
 
-impl SolutionLinear<Vec<Direction>, i32, i32> for Day9Solution {
-    fn load(input: &str) -> Result<Vec<Direction>> {
-        Ok(input
-            .lines()
-            .map(|x| {
-                let parts = x.split(" ").collect_vec();
-                let num = parts[1].parse::<usize>();
-                Direction::make(parts[0], num.unwrap())
-            })
-            .collect_vec())
+impl SolutionLinear<Vec<Motion>, i32, i32> for Day9Solution {
+    fn load(input: &str) -> Result<Vec<Motion>> {
+        Ok(parse_lines::<Motion>(input)?)
     }
 
-    fn part1(input: &mut Vec<Direction>) -> Result<i32> {
-        let result = simulate(input.to_vec(), 2);
-        Ok(result)
+    fn part1(input: &mut Vec<Motion>) -> Result<i32> {
+        simulate(input.to_vec(), 2)
     }
 
-    fn part2(input: &mut Vec<Direction>, _part_1_solution: i32) -> Result<i32> {
-        let result = simulate(input.to_vec(), 10);
-        Ok(result)
+    fn part2(input: &mut Vec<Motion>, _part_1_solution: i32) -> Result<i32> {
+        simulate(input.to_vec(), 10)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day9Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day9Solution;
+    (
         "R 4
 U 4
 L 3
@@ -154,8 +189,8 @@ L 5
 R 2",
         13,
         1
-    )]
-    #[case(
+    ),
+    (
         "R 5
 U 8
 L 8
@@ -166,16 +201,8 @@ L 25
 U 20",
         88,
         36
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day9Solution::load(input).unwrap();
-        let p1 = Day9Solution::part1(&mut input).unwrap();
-        let p2 = Day9Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
 
 /*
 ..........................
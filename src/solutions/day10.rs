@@ -1,10 +1,15 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::{
+    input_error::InputError,
+    simulation::{run_to_completion, Simulation},
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Day10Solution {}
 
-pub fn day10(input: &str) -> Result<f32> {
+pub fn day10(input: &str) -> Result<Solved> {
     solve_linear::<Day10Solution, _, _, _>(input)
 }
 
@@ -17,115 +22,156 @@ enum Instruction {
 impl Instruction {
     fn parse(input: &str) -> Result<Instruction> {
         let parts = input.split(" ").collect_vec();
-        match parts[0] {
-            "addx" => {
-                let num = parts[1].parse::<i32>()?;
+        match parts[..] {
+            ["addx", num] => {
+                let num = num.parse::<i32>()?;
                 return Ok(Instruction::Addx(num));
             }
-            "noop" => {
+            ["noop"] => {
                 return Ok(Instruction::Noop);
             }
-            _ => Err(anyhow!("Unknown seq {0}", parts[0])),
+            _ => Err(anyhow!("Unknown seq {0}", input)),
         }
     }
 }
 
-impl SolutionLinear<Vec<Instruction>, i32, String> for Day10Solution {
-    fn load(input: &str) -> Result<Vec<Instruction>> {
-        let mut output: Vec<Instruction> = Vec::new();
+/// Runs the CPU one clock cycle per step, tracking the signal strength and drawing the CRT
+pub struct CpuSimulation {
+    instructions: Vec<Instruction>,
+    pc: usize,
+    clock_cycle: i32,
+    x: i32,
+    instr_in_progress: bool,
+    signal_strength: i32,
+    screen: Vec<Vec<char>>,
+    row: Vec<char>,
+}
 
-        for line in input.lines() {
-            output.push(Instruction::parse(line)?)
+impl CpuSimulation {
+    fn new(instructions: Vec<Instruction>) -> Self {
+        Self {
+            instructions,
+            pc: 0,
+            clock_cycle: 0,
+            x: 1,
+            instr_in_progress: false,
+            signal_strength: 0,
+            screen: Vec::new(),
+            row: Vec::new(),
         }
+    }
 
-        Ok(output)
+    fn screen(&self) -> String {
+        self.screen.iter().map(|row| row.iter().join("")).join("\n")
     }
+}
 
-    fn part1(input: &mut Vec<Instruction>) -> Result<i32> {
-        let mut pc = 0;
-        let mut clock_cycle = 0;
-        let mut x = 1;
-        let mut instr_in_progress = false;
-        let mut signal_strength = 0;
+impl Simulation for CpuSimulation {
+    fn step(&mut self) -> Result<()> {
+        self.clock_cycle += 1;
 
-        // Each loop should be one clock cycle
-        loop {
-            clock_cycle += 1;
+        if self.clock_cycle % 40 == 20 {
+            self.signal_strength += self.clock_cycle * self.x;
+        }
 
-            if clock_cycle % 40 == 20 {
-                signal_strength += (clock_cycle) * x;
-            }
+        let idx = (self.clock_cycle - 1) % 40;
+        if idx >= self.x - 1 && idx <= self.x + 1 {
+            self.row.push('#');
+        } else {
+            self.row.push('.');
+        }
 
-            match input[pc] {
-                Instruction::Addx(val) => {
-                    if instr_in_progress {
-                        instr_in_progress = false;
-                        x += val;
-                        pc += 1;
-                    } else {
-                        instr_in_progress = true;
-                    }
-                }
-                Instruction::Noop => {
-                    pc += 1;
+        if self.clock_cycle % 40 == 0 {
+            self.screen.push(std::mem::take(&mut self.row));
+        }
+
+        match self.instructions[self.pc] {
+            Instruction::Addx(val) => {
+                if self.instr_in_progress {
+                    self.instr_in_progress = false;
+                    self.x += val;
+                    self.pc += 1;
+                } else {
+                    self.instr_in_progress = true;
                 }
             }
-
-            if pc >= input.len() {
-                break;
+            Instruction::Noop => {
+                self.pc += 1;
             }
         }
 
-        println!("Signal strength: {signal_strength}");
+        Ok(())
+    }
 
-        Ok(signal_strength)
+    fn is_done(&self) -> bool {
+        self.pc >= self.instructions.len()
     }
 
-    fn part2(input: &mut Vec<Instruction>, _part_1_solution: i32) -> Result<String> {
-        let mut pc = 0;
-        let mut clock_cycle = 0;
-        let mut x = 1;
-        let mut instr_in_progress = false;
-        let mut screen: Vec<Vec<char>> = Vec::new();
-        let mut row: Vec<char> = Vec::new();
+    fn render(&self) -> String {
+        let next = match self.instructions.get(self.pc) {
+            Some(instruction) => format!("{:?}", instruction),
+            None => "end of program".to_string(),
+        };
+        let sprite = (0..40)
+            .map(|i| if (i - self.x).abs() <= 1 { '#' } else { '.' })
+            .collect::<String>();
 
-        // Each loop should be one clock cycle
-        loop {
-            clock_cycle += 1;
+        format!(
+            "cycle {}, x = {}, next: {}\nsprite: {}\n{}\n{}",
+            self.clock_cycle,
+            self.x,
+            next,
+            sprite,
+            self.screen(),
+            self.row.iter().collect::<String>()
+        )
+    }
 
-            let idx = (clock_cycle - 1) % 40;
-            if idx >= x - 1 && idx <= x + 1 {
-                row.push('#');
-            } else {
-                row.push('.');
-            }
+    fn watches(&self) -> Vec<(String, i64)> {
+        vec![
+            ("cycle".to_string(), self.clock_cycle.into()),
+            ("x".to_string(), self.x.into()),
+            ("pc".to_string(), self.pc as i64),
+            ("signal".to_string(), self.signal_strength.into()),
+        ]
+    }
+}
 
-            if clock_cycle % 40 == 0 {
-                screen.push(row);
-                row = Vec::new();
-            }
+/// Build the CPU simulation for stepping through in the debugger. Both parts run the same program
+pub fn simulation(input: &str, part: usize) -> Result<Box<dyn Simulation>> {
+    if part != 1 && part != 2 {
+        return Err(anyhow!("Day 10 has no part {}", part));
+    }
+    Ok(Box::new(CpuSimulation::new(Day10Solution::load(input)?)))
+}
 
-            match input[pc] {
-                Instruction::Addx(val) => {
-                    if instr_in_progress {
-                        instr_in_progress = false;
-                        x += val;
-                        pc += 1;
-                    } else {
-                        instr_in_progress = true;
-                    }
-                }
-                Instruction::Noop => {
-                    pc += 1;
-                }
-            }
+impl SolutionLinear<Vec<Instruction>, i32, String> for Day10Solution {
+    fn load(input: &str) -> Result<Vec<Instruction>> {
+        let mut output: Vec<Instruction> = Vec::new();
 
-            if pc >= input.len() {
-                break;
-            }
+        for (i, line) in input.lines().enumerate() {
+            let instruction = Instruction::parse(line)
+                .map_err(|err| InputError::at_line(input, i + 1, err.to_string()))?;
+            output.push(instruction)
         }
 
-        let result = screen.iter().map(|row| row.iter().join("")).join("\n");
+        Ok(output)
+    }
+
+    fn part1(input: &mut Vec<Instruction>) -> Result<i32> {
+        let mut sim = CpuSimulation::new(input.clone());
+        run_to_completion(&mut sim)?;
+
+        println!("Signal strength: {}", sim.signal_strength);
+
+        Ok(sim.signal_strength)
+    }
+
+    fn part2(input: &mut Vec<Instruction>, _part_1_solution: i32) -> Result<String> {
+        let mut sim = CpuSimulation::new(input.clone());
+        run_to_completion(&mut sim)?;
+
+        let result = sim.screen();
 
         println!("{result}");
 
@@ -133,14 +179,8 @@ impl SolutionLinear<Vec<Instruction>, i32, String> for Day10Solution {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day10Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day10Solution;
+    (
         "addx 15
 addx -11
 addx 6
@@ -294,20 +334,12 @@ noop",
 #####.....#####.....#####.....#####.....
 ######......######......######......####
 #######.......#######.......#######....."
-    )]
-    #[case(
+    ),
+    (
         "noop
 addx 3
 addx -5",
         0,
         ""
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: String) {
-        let mut input = Day10Solution::load(input).unwrap();
-        let p1 = Day10Solution::part1(&mut input).unwrap();
-        let p2 = Day10Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
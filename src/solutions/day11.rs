@@ -1,12 +1,20 @@
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
-use anyhow::{anyhow, Result};
+use std::str::FromStr;
+
+use crate::aoc_examples;
+use crate::utils::{
+    checked,
+    from_line::{parse_blocks, FromLine},
+    input_error::InputError,
+    simulation::{run_to_completion, Simulation},
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
+use anyhow::{anyhow, Context, Result};
 use itertools::Itertools;
 use num::{integer, Integer};
-use regex::Regex;
 
 pub struct Day11Solution {}
 
-pub fn day11(input: &str) -> Result<f32> {
+pub fn day11(input: &str) -> Result<Solved> {
     solve_linear::<Day11Solution, _, _, _>(input)
 }
 
@@ -16,14 +24,18 @@ enum Val {
     Own,
 }
 
-impl Val {
-    fn make(input: &str) -> Val {
+impl FromStr for Val {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Val> {
         if input == "old" {
-            return Val::Own;
+            return Ok(Val::Own);
         }
-        let val = input.parse::<i64>().unwrap();
-        Val::Num(val)
+        Ok(Val::Num(input.parse::<i64>()?))
     }
+}
+
+impl Val {
     fn get(&self, old: i64) -> i64 {
         match self {
             Val::Num(num) => {
@@ -42,30 +54,38 @@ enum Op {
     Sub(Val, Val),
     Mul(Val, Val),
 }
-impl Op {
-    fn make(left: &str, op: &str, right: &str) -> Op {
-        let left_parsed = Val::make(left);
-        let right_parsed = Val::make(right);
+/// Parses an operation like `old * 19`
+impl FromStr for Op {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Op> {
+        let (left, op, right) = input
+            .split_whitespace()
+            .collect_tuple()
+            .ok_or_else(|| anyhow!("Expected an operation like 'old * 19', got '{}'", input))?;
+        let left_parsed = left.parse::<Val>()?;
+        let right_parsed = right.parse::<Val>()?;
 
         match op {
-            "+" => return Op::Add(left_parsed, right_parsed),
-            "-" => return Op::Sub(left_parsed, right_parsed),
-            "*" => return Op::Mul(left_parsed, right_parsed),
-            _ => {
-                panic!();
-            }
+            "+" => Ok(Op::Add(left_parsed, right_parsed)),
+            "-" => Ok(Op::Sub(left_parsed, right_parsed)),
+            "*" => Ok(Op::Mul(left_parsed, right_parsed)),
+            _ => Err(anyhow!("Unknown operator '{}'", op)),
         }
     }
-    fn get(&self, old: i64) -> i64 {
+}
+
+impl Op {
+    fn get(&self, old: i64) -> Result<i64> {
         match self {
             Op::Add(left, right) => {
-                return left.get(old) + right.get(old);
+                return checked::add(left.get(old), right.get(old), "a worry level");
             }
             Op::Sub(left, right) => {
-                return left.get(old) - right.get(old);
+                return checked::sub(left.get(old), right.get(old), "a worry level");
             }
             Op::Mul(left, right) => {
-                return left.get(old) * right.get(old);
+                return checked::mul(left.get(old), right.get(old), "a worry level");
             }
         }
     }
@@ -95,191 +115,230 @@ struct Monkey {
     tester: ThrowTester,
 }
 
-impl SolutionLinear<Vec<Monkey>, i64, i64> for Day11Solution {
-    fn load(input: &str) -> Result<Vec<Monkey>> {
-        let unparsed_monkeys = input.lines().chunks(7);
-
-        let head_re = Regex::new(r"^Monkey (\d):$").unwrap();
-        let items_re = Regex::new(r"^ *Starting items: (\d+(?:, \d+)*)$").unwrap();
-        let op_re = Regex::new(r"^ *Operation: new = (old|\d+) (\+|\-|\*) (old|\d+)$").unwrap();
-        let test_re = Regex::new(r"^ *Test: divisible by (\d+)$").unwrap();
-        let test_true_re = Regex::new(r"^ *If true: throw to monkey (\d+)$").unwrap();
-        let test_false_re = Regex::new(r"^ *If false: throw to monkey (\d+)$").unwrap();
+/// A monkey, as written in the puzzle input
+#[derive(Debug, FromLine)]
+#[pattern(
+    r"^Monkey (\d+):
+ *Starting items: (.*)
+ *Operation: new = (.+)
+ *Test: divisible by (\d+)
+ *If true: throw to monkey (\d+)
+ *If false: throw to monkey (\d+)$"
+)]
+struct MonkeyNotes {
+    id: usize,
+    items: Vec<i64>,
+    op: Op,
+    is_div_by: i64,
+    true_target: usize,
+    false_target: usize,
+}
 
-        let mut parsed_monkeys: Vec<Monkey> = Vec::new();
+impl From<MonkeyNotes> for Monkey {
+    fn from(notes: MonkeyNotes) -> Self {
+        Monkey {
+            items: notes.items,
+            op: notes.op,
+            tester: ThrowTester {
+                is_div_by: notes.is_div_by,
+                true_target: notes.true_target,
+                false_target: notes.false_target,
+            },
+        }
+    }
+}
 
-        let mut idx = 0;
+/// How worry levels are kept in check after each inspection
+#[derive(Debug, Clone, Copy)]
+enum Relief {
+    /// part 1: the item is undamaged, so worry drops to a third
+    DivideBy3,
+    /// part 2: worry is kept small by taking it modulo the product of all the tests
+    Modulo(i64),
+}
 
-        for unparsed_monkey in unparsed_monkeys.into_iter() {
-            let mut unparsed_monkey_vec = unparsed_monkey.collect_vec();
+/// Plays one round of keep away per step
+struct MonkeySimulation {
+    monkeys: Vec<Monkey>,
+    inspection_counts: Vec<i64>,
+    round: usize,
+    rounds: usize,
+    relief: Relief,
+}
 
-            if unparsed_monkey_vec.len() == 7 {
-                if unparsed_monkey_vec[6] == "" {
-                    unparsed_monkey_vec.pop();
-                }
-            }
+impl MonkeySimulation {
+    fn new(monkeys: Vec<Monkey>, rounds: usize, relief: Relief) -> Self {
+        Self {
+            inspection_counts: vec![0; monkeys.len()],
+            monkeys,
+            round: 0,
+            rounds,
+            relief,
+        }
+    }
 
-            if unparsed_monkey_vec.len() != 6 {
-                return Err(anyhow!("Unparsed monkey was not of length 6"));
-            }
+    /// the product of the two highest inspection counts
+    fn monkey_business(&self) -> Result<i64> {
+        let mut inspection_counts = self.inspection_counts.clone();
+        inspection_counts.sort();
 
-            let head_match = head_re.captures(unparsed_monkey_vec[0]).unwrap();
-            let monkey_num = head_match[1].to_string().parse::<usize>().unwrap();
-            assert!(idx == monkey_num);
-
-            let items_match = items_re.captures(unparsed_monkey_vec[1]).unwrap();
-            let items_str = items_match[1].to_string();
-            let items = items_str
-                .split(", ")
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect_vec();
-
-            let op_match = op_re.captures(unparsed_monkey_vec[2]).unwrap();
-            let op = Op::make(&op_match[1], &op_match[2], &op_match[3]);
-
-            let test_match = test_re.captures(unparsed_monkey_vec[3]).unwrap();
-            let test_val = test_match[1].parse::<i64>().unwrap();
-
-            let test_true_match = test_true_re.captures(unparsed_monkey_vec[4]).unwrap();
-            let test_true_val = test_true_match[1].parse::<usize>().unwrap();
-
-            let test_false_match = test_false_re.captures(unparsed_monkey_vec[5]).unwrap();
-            let test_false_val = test_false_match[1].parse::<usize>().unwrap();
-
-            let tester = ThrowTester {
-                is_div_by: test_val,
-                true_target: test_true_val,
-                false_target: test_false_val,
-            };
-            let monkey = Monkey {
-                items: items,
-                op: op,
-                tester: tester,
-            };
-            parsed_monkeys.push(monkey);
-
-            idx += 1;
+        if inspection_counts.len() < 2 {
+            return Err(anyhow!("Need at least 2 monkeys"));
         }
-        println!("{parsed_monkeys:?}");
 
-        Ok(parsed_monkeys)
+        checked::mul(
+            inspection_counts[inspection_counts.len() - 1],
+            inspection_counts[inspection_counts.len() - 2],
+            "the monkey business",
+        )
     }
+}
 
-    fn part1(input: &mut Vec<Monkey>) -> Result<i64> {
-        let mut monkeys = input.clone();
-        let mut round = 1;
-        let mut inspection_counts: Vec<i64> = vec![0; monkeys.len()];
-        loop {
-            for i in 0..monkeys.len() {
-                loop {
-                    if monkeys[i].items.len() < 1 {
-                        break;
-                    }
-
-                    let item = monkeys[i].items[0];
-                    let inspection_result = input[i].op.get(item);
-                    let bored_result = inspection_result / 3;
-                    let throw_target = input[i].tester.throw_to(bored_result);
-                    inspection_counts[i] += 1;
-
-                    monkeys[i].items.remove(0);
-                    monkeys[throw_target].items.push(bored_result);
-                }
-            }
-
-            // println!("Result of round {round}");
-            // for i in 0..monkeys.len() {
-            //     print!("Monkey {i}: ");
-            //     println!("{:?}", monkeys[i].items);
-            // }
-
-            if round >= 20 {
-                break;
+impl Simulation for MonkeySimulation {
+    fn step(&mut self) -> Result<()> {
+        for i in 0..self.monkeys.len() {
+            let items = std::mem::take(&mut self.monkeys[i].items);
+            for item in items {
+                let inspection_result = self.monkeys[i]
+                    .op
+                    .get(item)
+                    .with_context(|| format!("Monkey {} inspecting item {}", i, item))?;
+                let new_item = match self.relief {
+                    Relief::DivideBy3 => inspection_result / 3,
+                    Relief::Modulo(lcm_val) => inspection_result % lcm_val,
+                };
+                let throw_target = self.monkeys[i].tester.throw_to(new_item);
+                self.inspection_counts[i] += 1;
+
+                self.monkeys
+                    .get_mut(throw_target)
+                    .ok_or_else(|| {
+                        anyhow!("Monkey {} threw to unknown monkey {}", i, throw_target)
+                    })?
+                    .items
+                    .push(new_item);
             }
-
-            round += 1;
         }
 
-        inspection_counts.sort();
+        self.round += 1;
 
-        assert!(inspection_counts.len() >= 2);
+        Ok(())
+    }
 
-        let result = inspection_counts[inspection_counts.len() - 1]
-            * inspection_counts[inspection_counts.len() - 2];
-        println!("{}", result);
-        Ok(result)
+    fn is_done(&self) -> bool {
+        self.round >= self.rounds
     }
 
-    fn part2(input: &mut Vec<Monkey>, _part_1_solution: i64) -> Result<i64> {
-        let mut monkeys = input.clone();
-        let mut round = 1;
-        let mut inspection_counts: Vec<i64> = vec![0; monkeys.len()];
+    fn render(&self) -> String {
+        let mut output = format!("After round {}:\n", self.round);
+        for (i, monkey) in self.monkeys.iter().enumerate() {
+            output += &format!(
+                "Monkey {} (inspected {} items): {}\n",
+                i,
+                self.inspection_counts[i],
+                monkey.items.iter().join(", ")
+            );
+        }
+        output
+    }
 
-        // Fascinating
-        let mut lcm_val = 1;
-        for i in 0..monkeys.len() {
-            lcm_val = lcm_val.lcm(&monkeys[i].tester.is_div_by);
-            print!("{} ", &monkeys[i].tester.is_div_by);
+    fn watches(&self) -> Vec<(String, i64)> {
+        let mut watches = vec![("round".to_string(), self.round as i64)];
+        for (i, count) in self.inspection_counts.iter().enumerate() {
+            watches.push((format!("inspected_{}", i), *count));
         }
-        println!("LCM: {}", lcm_val);
+        watches
+    }
+}
 
-        loop {
-            for i in 0..monkeys.len() {
-                loop {
-                    if monkeys[i].items.len() < 1 {
-                        break;
-                    }
+/// Build the keep away simulation for the given part, for stepping through in the debugger
+pub fn simulation(input: &str, part: usize) -> Result<Box<dyn Simulation>> {
+    let monkeys = Day11Solution::load(input)?;
+    match part {
+        1 => Ok(Box::new(MonkeySimulation::new(
+            monkeys,
+            20,
+            Relief::DivideBy3,
+        ))),
+        2 => {
+            let lcm_val = monkeys
+                .iter()
+                .fold(1, |acc, monkey| acc.lcm(&monkey.tester.is_div_by));
+            Ok(Box::new(MonkeySimulation::new(
+                monkeys,
+                10000,
+                Relief::Modulo(lcm_val),
+            )))
+        }
+        _ => Err(anyhow!("Day 11 has no part {}", part)),
+    }
+}
 
-                    let item = monkeys[i].items[0];
-                    // println!("{:?} {}", input[i].op, item);
-                    let inspection_result = input[i].op.get(item);
-                    let small_result = inspection_result % lcm_val;
+impl SolutionLinear<Vec<Monkey>, i64, i64> for Day11Solution {
+    fn load(input: &str) -> Result<Vec<Monkey>> {
+        let notes = parse_blocks::<MonkeyNotes>(input)?;
 
-                    let throw_target = input[i].tester.throw_to(inspection_result);
-                    inspection_counts[i] += 1;
+        let mut parsed_monkeys: Vec<Monkey> = Vec::new();
+        for (idx, monkey_notes) in notes.into_iter().enumerate() {
+            if monkey_notes.id != idx {
+                let header = format!("Monkey {}:", monkey_notes.id);
+                let line = input
+                    .lines()
+                    .position(|line| line.trim() == header)
+                    .map_or(1, |i| i + 1);
+                return Err(InputError::at_line(
+                    input,
+                    line,
+                    format!("Expected monkey {}, found monkey {}", idx, monkey_notes.id),
+                )
+                .into());
+            }
+            if monkey_notes.is_div_by == 0 {
+                return Err(anyhow!("Monkey {} tests divisibility by 0", idx));
+            }
+            parsed_monkeys.push(monkey_notes.into());
+        }
 
-                    monkeys[i].items.remove(0);
-                    monkeys[throw_target].items.push(small_result);
+        for (idx, monkey) in parsed_monkeys.iter().enumerate() {
+            for target in [monkey.tester.true_target, monkey.tester.false_target] {
+                if target >= parsed_monkeys.len() || target == idx {
+                    return Err(anyhow!("Monkey {} can't throw to monkey {}", idx, target));
                 }
             }
+        }
+        println!("{parsed_monkeys:?}");
 
-            // if round % 1000 == 0 {
-            //     println!("Result of round {round}");
-            //     for i in 0..monkeys.len() {
-            //         print!("Monkey {i}: ");
-            //         println!("{:?}", monkeys[i].items);
-            //     }
-            // }
-
-            if round >= 10000 {
-                break;
-            }
+        Ok(parsed_monkeys)
+    }
 
-            round += 1;
-        }
+    fn part1(input: &mut Vec<Monkey>) -> Result<i64> {
+        let mut sim = MonkeySimulation::new(input.clone(), 20, Relief::DivideBy3);
+        run_to_completion(&mut sim)?;
 
-        inspection_counts.sort();
+        let result = sim.monkey_business()?;
+        println!("{}", result);
+        Ok(result)
+    }
 
-        assert!(inspection_counts.len() >= 2);
+    fn part2(input: &mut Vec<Monkey>, _part_1_solution: i64) -> Result<i64> {
+        // Fascinating
+        let mut lcm_val = 1;
+        for monkey in input.iter() {
+            lcm_val = lcm_val.lcm(&monkey.tester.is_div_by);
+        }
+        println!("LCM: {}", lcm_val);
 
-        println!("{:?}", inspection_counts);
+        let mut sim = MonkeySimulation::new(input.clone(), 10000, Relief::Modulo(lcm_val));
+        run_to_completion(&mut sim)?;
 
-        let a: i64 = inspection_counts[inspection_counts.len() - 1].into();
-        let b: i64 = inspection_counts[inspection_counts.len() - 2].into();
-        let result: i64 = a * b;
+        let result = sim.monkey_business()?;
         println!("{}", result);
         Ok(result)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day11Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day11Solution;
+    (
         "Monkey 0:
 Starting items: 79, 98
 Operation: new = old * 19
@@ -309,13 +368,5 @@ Test: divisible by 17
     If false: throw to monkey 1",
         10605,
         2713310158
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i64, #[case] expected_2: i64) {
-        let mut input = Day11Solution::load(input).unwrap();
-        let p1 = Day11Solution::part1(&mut input).unwrap();
-        let p2 = Day11Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+    ),
+);
@@ -1,174 +1,42 @@
 use std::{cmp::Ordering, collections::VecDeque};
 
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::{
+    parser::{blocks, int, line_ending, nested, parse_all, Input, Nested, ParseResult, Parser},
+    solver_types::{solve_linear, SolutionLinear, Solved},
+};
 use anyhow::{anyhow, Result};
-use itertools::Itertools;
 
 pub struct Day13Solution {}
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
-enum Token {
-    LBRA,
-    RBRA,
-    Num(i32),
-    COMMA,
-    NumTemp,
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Item {
     Num(i32),
     List(VecDeque<Item>),
 }
 
-pub fn day13(input: &str) -> Result<f32> {
+pub fn day13(input: &str) -> Result<Solved> {
     solve_linear::<Day13Solution, _, i32, i32>(input)
 }
 
-/*
-item := "[" list "]"
-      | num
-*/
-fn parse_item(mut input: VecDeque<Token>) -> (Item, VecDeque<Token>) {
-    let item = input.pop_front().unwrap();
-    let result: Item;
-    match item {
-        Token::LBRA => {
-            (result, input) = parse_list(input);
-            let rbra_test = input.pop_front().unwrap();
-            if rbra_test != Token::RBRA {
-                panic!();
-            }
-        }
-        Token::Num(_) => {
-            input.push_front(item);
-            (result, input) = parse_num(input);
-        }
-        _ => {
-            panic!("Got {:?}, expected LBRA or NUM", item);
-        }
-    }
-    (result, input)
-}
-
-/*
-list := item list_prime
-      | e
-*/
-
-fn parse_list(mut input: VecDeque<Token>) -> (Item, VecDeque<Token>) {
-    let tester = input.pop_front().unwrap();
-
-    match tester {
-        Token::LBRA | Token::Num(_) => {
-            input.push_front(tester);
-        }
-        _ => {
-            input.push_front(tester);
-            return (Item::List(VecDeque::new()), input);
-        }
-    }
-
-    let item;
-    (item, input) = parse_item(input);
-    let remaining_list;
-    (remaining_list, input) = parse_list_prime(input);
-
-    match remaining_list {
-        None => {
-            let mut vecde = VecDeque::new();
-            vecde.push_back(item);
-            return (Item::List(vecde), input);
-        }
-        Some(mut list) => {
-            list.push_front(item);
-            return (Item::List(list), input);
-        }
-    }
-}
-
-/*
-list_prime := "," item list_prime
-             | e
- */
-fn parse_list_prime(mut input: VecDeque<Token>) -> (Option<VecDeque<Item>>, VecDeque<Token>) {
-    let comma_test = input.pop_front().unwrap();
-    if comma_test != Token::COMMA {
-        input.push_front(comma_test);
-        return (None, input);
-    }
-
-    let item;
-    (item, input) = parse_item(input);
-
-    let remaining_list;
-    (remaining_list, input) = parse_list_prime(input);
-
-    match remaining_list {
-        None => {
-            let mut vecde = VecDeque::new();
-            vecde.push_back(item);
-            return (Some(vecde), input);
-        }
-        Some(mut list) => {
-            list.push_front(item);
-            return (Some(list), input);
+impl From<Nested<i32>> for Item {
+    fn from(nested: Nested<i32>) -> Self {
+        match nested {
+            Nested::Leaf(num) => Item::Num(num),
+            Nested::List(items) => Item::List(items.into_iter().map(Item::from).collect()),
         }
     }
 }
 
-// num := [0-9]+
-fn parse_num(mut input: VecDeque<Token>) -> (Item, VecDeque<Token>) {
-    let item = input.pop_front().unwrap();
-    match item {
-        Token::Num(num) => return (Item::Num(num), input),
-        _ => {
-            panic!("Got {:?}, expecting num", item)
-        }
-    }
+/// A packet, e.g. `[1,[2,3],4]`
+fn packet(input: Input) -> ParseResult<Item> {
+    nested("[", ",", "]", int::<i32>())
+        .map(Item::from)
+        .parse(input)
 }
 
-fn lex(input: &str) -> Vec<Token> {
-    let mut output: Vec<Token> = Vec::new();
-    let mut num = 0;
-
-    for item in input.chars() {
-        if (item == '[' || item == ']' || item == ',')
-            && output.len() >= 1
-            && output[output.len() - 1] == Token::NumTemp
-        {
-            output.pop();
-            output.push(Token::Num(num));
-            num = 0;
-        }
-        if item == '[' {
-            output.push(Token::LBRA);
-        } else if item == ']' {
-            output.push(Token::RBRA);
-        } else if item == ',' {
-            output.push(Token::COMMA);
-        } else if (item as i32) >= '0' as i32 && (item as i32) <= '9' as i32 {
-            if output[output.len() - 1] != Token::NumTemp {
-                output.push(Token::NumTemp);
-            }
-            num *= 10;
-            num += (item as i32) - ('0' as i32);
-        } else {
-            panic!("Unknown character {}", item);
-        }
-    }
-
-    output
-}
-
-fn process(input: &str) -> Item {
-    let lex_output = lex(input);
-
-    let vedeq = VecDeque::from(lex_output);
-
-    let (parse_result, _) = parse_item(vedeq);
-
-    parse_result
+fn process(input: &str) -> Result<Item> {
+    Ok(parse_all(&packet, input)?)
 }
 
 fn compare_pair(pair: (Item, Item)) -> Option<bool> {
@@ -221,24 +89,8 @@ fn compare_lists(list_a: VecDeque<Item>, list_b: VecDeque<Item>) -> Option<bool>
 
 impl SolutionLinear<Vec<(Item, Item)>, i32, i32> for Day13Solution {
     fn load(input: &str) -> Result<Vec<(Item, Item)>> {
-        let raw_groups = input.lines().group_by(|x| x.to_string() == "");
-        let groups = raw_groups.into_iter().filter_map(|(key, group)| {
-            if key == true {
-                None
-            } else {
-                Some(group.collect_vec())
-            }
-        });
-
-        let mut output: Vec<(Item, Item)> = Vec::new();
-
-        for item in groups {
-            assert_eq!(item.len(), 2);
-
-            let a = process(item[0]);
-            let b = process(item[1]);
-            output.push((a, b));
-        }
+        let pair = packet.skip(line_ending()).and(packet);
+        let output = parse_all(&blocks(pair), input)?;
 
         Ok(output)
     }
@@ -266,9 +118,9 @@ impl SolutionLinear<Vec<(Item, Item)>, i32, i32> for Day13Solution {
             packets.push(b);
         }
 
-        let divider_1 = process("[[2]]");
+        let divider_1 = process("[[2]]")?;
         packets.push(divider_1.clone());
-        let divider_2 = process("[[6]]");
+        let divider_2 = process("[[6]]")?;
         packets.push(divider_2.clone());
 
         packets.sort_by(|a, b| {
@@ -303,14 +155,8 @@ impl SolutionLinear<Vec<(Item, Item)>, i32, i32> for Day13Solution {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day13Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day13Solution;
+    (
         "[1,1,3,1,1]
 [1,1,5,1,1]
 
@@ -336,13 +182,139 @@ mod tests {
 [1,[2,[3,[4,[5,6,0]]]],8,9]",
         13,
         140
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: i32, #[case] expected_2: i32) {
-        let mut input = Day13Solution::load(input).unwrap();
-        let p1 = Day13Solution::part1(&mut input).unwrap();
-        let p2 = Day13Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
+    ),
+);
+
+#[cfg(test)]
+mod tests {
+    use std::{cmp::Ordering, collections::VecDeque};
+
+    use super::{compare_pair, process, Day13Solution, Item};
+    use crate::utils::property::{check, check_solution, Oracle, Rng, Shrink, DEFAULT_CASES};
+    use itertools::Itertools;
+
+    impl Shrink for Item {
+        fn shrink(&self) -> Vec<Self> {
+            match self {
+                Item::Num(num) => num.shrink().into_iter().map(Item::Num).collect(),
+                Item::List(items) => {
+                    // try replacing the list with one of its items, then simplify the list itself
+                    let mut candidates = items.iter().cloned().collect_vec();
+                    candidates.extend(
+                        Vec::from(items.clone())
+                            .shrink()
+                            .into_iter()
+                            .map(|items| Item::List(items.into())),
+                    );
+                    candidates
+                }
+            }
+        }
+    }
+
+    fn generate_item(rng: &mut Rng, depth: usize) -> Item {
+        if depth == 0 || rng.below(3) == 0 {
+            Item::Num(rng.range(0, 10) as i32)
+        } else {
+            Item::List(rng.vec(0, 4, |rng| generate_item(rng, depth - 1)).into())
+        }
+    }
+
+    fn generate_packet(rng: &mut Rng) -> Item {
+        Item::List(rng.vec(0, 4, |rng| generate_item(rng, 3)).into())
+    }
+
+    fn format_item(item: &Item) -> String {
+        match item {
+            Item::Num(num) => num.to_string(),
+            Item::List(items) => format!("[{}]", items.iter().map(format_item).join(",")),
+        }
+    }
+
+    /// the packet ordering, written as directly as possible from the puzzle description
+    fn reference_cmp(a: &Item, b: &Item) -> Ordering {
+        match (a, b) {
+            (Item::Num(a), Item::Num(b)) => a.cmp(b),
+            (Item::List(a), Item::List(b)) => a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| reference_cmp(a, b))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Item::Num(a), b) => reference_cmp(&Item::List(VecDeque::from([Item::Num(*a)])), b),
+            (a, Item::Num(b)) => reference_cmp(a, &Item::List(VecDeque::from([Item::Num(*b)]))),
+        }
+    }
+
+    fn dividers() -> [Item; 2] {
+        [process("[[2]]").unwrap(), process("[[6]]").unwrap()]
+    }
+
+    const ORACLE: Oracle<Vec<(Item, Item)>, i32, i32> = Oracle {
+        generate: |rng| rng.vec(0, 8, |rng| (generate_packet(rng), generate_packet(rng))),
+        // packets are always lists, no pair is in an undecidable order,
+        // and nothing could be confused with a divider
+        valid: |pairs| {
+            pairs.iter().all(|(a, b)| {
+                matches!((a, b), (Item::List(_), Item::List(_)))
+                    && reference_cmp(a, b) != Ordering::Equal
+                    && dividers().iter().all(|divider| {
+                        reference_cmp(a, divider) != Ordering::Equal
+                            && reference_cmp(b, divider) != Ordering::Equal
+                    })
+            })
+        },
+        to_input: |pairs| {
+            pairs
+                .iter()
+                .map(|(a, b)| format!("{}\n{}", format_item(a), format_item(b)))
+                .join("\n\n")
+        },
+        part1: |pairs| {
+            pairs
+                .iter()
+                .enumerate()
+                .filter(|(_, (a, b))| reference_cmp(a, b) == Ordering::Less)
+                .map(|(i, _)| i as i32 + 1)
+                .sum()
+        },
+        part2: |pairs| {
+            let packets = pairs.iter().flat_map(|(a, b)| [a, b]).collect_vec();
+            let [divider_1, divider_2] = dividers();
+            let before = |divider: &Item| {
+                packets
+                    .iter()
+                    .filter(|packet| reference_cmp(packet, divider) == Ordering::Less)
+                    .count() as i32
+            };
+            (before(&divider_1) + 1) * (before(&divider_2) + 2)
+        },
+    };
+
+    #[test]
+    fn compare_pair_matches_reference() {
+        check(
+            DEFAULT_CASES,
+            13,
+            |rng| (generate_item(rng, 4), generate_item(rng, 4)),
+            |(a, b)| {
+                let expected = reference_cmp(a, b);
+                let result = match compare_pair((a.clone(), b.clone())) {
+                    Some(true) => Ordering::Less,
+                    Some(false) => Ordering::Greater,
+                    None => Ordering::Equal,
+                };
+                if result == expected {
+                    Ok(())
+                } else {
+                    Err(format!("got {:?}, expected {:?}", result, expected))
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn matches_reference() {
+        check_solution::<Day13Solution, _, _, _, _>(DEFAULT_CASES, 13, &ORACLE);
     }
 }
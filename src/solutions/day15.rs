@@ -1,168 +1,137 @@
-use std::{
-    cell,
-    cmp::Ordering,
-    collections::HashSet,
-    fmt::{write, Display},
+use std::collections::HashSet;
+
+use crate::aoc_examples;
+use crate::utils::{
+    checked,
+    from_line::{parse_lines, FromLine},
+    manhattan_ball::ManhattanBall,
+    point::Pt,
+    progress::Progress,
+    solver_types::{solve_linear, SolutionLinear, Solved},
 };
-
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
-use regex::Regex;
 
 pub struct Day15Solution {}
 
-pub fn day15(input: &str) -> Result<f32> {
+pub fn day15(input: &str) -> Result<Solved> {
     solve_linear::<Day15Solution, _, _, _>(input)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: isize,
-    y: isize,
+/// The first line of the input, which isn't part of the real puzzle input.
+/// It gives the row to check in part 1, and the area to search in part 2
+#[derive(Debug, FromLine)]
+#[pattern(r"^target at y=(-?\d+); min_x=(-?\d+); min_y=(-?\d+); max_x=(-?\d+); max_y=(-?\d+)$")]
+struct Target {
+    row: isize,
+    min_x: isize,
+    min_y: isize,
+    max_x: isize,
+    max_y: isize,
 }
 
-impl Point {
-    fn distance_to(&self, other: &Point) -> isize {
-        (self.x - other.x).abs() + (self.y - other.y).abs()
-    }
+#[derive(Debug, FromLine)]
+#[pattern(r"^Sensor at (.+): closest beacon is at (.+)$")]
+struct Reading {
+    sensor: Pt<2>,
+    beacon: Pt<2>,
 }
 
-impl Display for Point {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write(f, format_args!("({},{})", self.x, self.y))
-    }
+/// the area each sensor can rule out, out to its closest beacon
+fn coverage(data: &[(Pt<2>, Pt<2>)]) -> Vec<ManhattanBall<2>> {
+    data.iter()
+        .map(|(sensor, beacon)| ManhattanBall::reaching(*sensor, *beacon))
+        .collect()
 }
 
 fn get_ranges_for_row(
-    data: &Vec<(Point, Point)>,
+    data: &Vec<(Pt<2>, Pt<2>)>,
     target_row: isize,
 ) -> Result<Vec<(isize, isize)>> {
-    let mut distances = Vec::new();
-    let mut beacons = HashSet::new();
-
-    for (sensor, beacon) in data.clone() {
-        let distance = sensor.distance_to(&beacon);
-        distances.push((sensor, distance));
-        beacons.insert(beacon);
-    }
-
-    let mut covered_ranges = Vec::new();
-
-    for (sensor, distance) in distances.clone() {
-        let y_dist = (sensor.y - target_row).abs();
-        let x_dist = distance - y_dist;
-
-        if x_dist < 0 {
-            continue;
-        }
-
-        let left_x = sensor.x - x_dist;
-        let right_x = sensor.x + x_dist;
-        covered_ranges.push((left_x, right_x));
-    }
-
-    if covered_ranges.len() == 0 {
-        return Ok(Vec::new());
-    }
-
-    covered_ranges.sort_by(|(a1, a2), (b1, b2)| match a1.cmp(b1) {
-        Ordering::Equal => return a2.cmp(b2),
-        a => {
-            return a;
-        }
-    });
-
-    // println!("Original {:?}", covered_ranges);
-
-    let mut resolved_ranges = Vec::new();
-
-    let mut curr_range = covered_ranges.remove(0);
-    let mut pushed_modified_range;
-
-    loop {
-        let next_range = covered_ranges.remove(0);
-
-        if curr_range.1 + 1 >= next_range.0 {
-            pushed_modified_range = false;
-            if curr_range.1 < next_range.1 {
-                // Skip reassignment if can overlapping
-                curr_range = (curr_range.0, next_range.1);
+    let covered_ranges = coverage(data)
+        .iter()
+        .filter_map(|ball| ball.row_span(target_row))
+        .sorted()
+        .collect_vec();
+
+    let mut resolved_ranges: Vec<(isize, isize)> = Vec::new();
+
+    for next_range in covered_ranges {
+        match resolved_ranges.last_mut() {
+            // Overlapping or touching the previous range, so extend it
+            Some(curr_range) if curr_range.1 + 1 >= next_range.0 => {
+                if curr_range.1 < next_range.1 {
+                    curr_range.1 = next_range.1;
+                }
             }
-        } else {
-            resolved_ranges.push(curr_range);
-            curr_range = next_range;
-            pushed_modified_range = true;
-        }
-
-        if covered_ranges.len() == 0 {
-            break;
+            _ => resolved_ranges.push(next_range),
         }
     }
 
-    if !pushed_modified_range {
-        resolved_ranges.push(curr_range)
-    }
-
     Ok(resolved_ranges)
 }
 
-impl SolutionLinear<(Vec<(Point, Point)>, isize, isize, isize, isize, isize), isize, isize>
+/// The only point in the box from `min` to `max` that no sensor covers, if it's at a corner of the
+/// box or where the rings just outside two sensors' coverage meet. That's where a lone uncovered
+/// point usually ends up, as it's hemmed in on every side
+fn find_uncovered(balls: &[ManhattanBall<2>], min: Pt<2>, max: Pt<2>) -> Option<Pt<2>> {
+    let corners = [min, max, Pt([min.0[0], max.0[1]]), Pt([max.0[0], min.0[1]])];
+    let crossings = balls
+        .iter()
+        .map(|ball| ball.grow(1))
+        .tuple_combinations()
+        .flat_map(|(a, b)| a.boundary_intersections(&b));
+
+    corners
+        .into_iter()
+        .chain(crossings)
+        .filter(|&pt| pt.component_max(min).component_min(max) == pt)
+        .find(|pt| !balls.iter().any(|ball| ball.contains(*pt)))
+}
+
+fn tuning_frequency(Pt([x, y]): Pt<2>) -> Result<isize> {
+    let frequency = checked::mul(x, 4000000, "the tuning frequency")?;
+    checked::add(frequency, y, "the tuning frequency")
+}
+
+impl SolutionLinear<(Vec<(Pt<2>, Pt<2>)>, isize, isize, isize, isize, isize), isize, isize>
     for Day15Solution
 {
-    fn load(input: &str) -> Result<(Vec<(Point, Point)>, isize, isize, isize, isize, isize)> {
-        let mut rows = input.lines().collect_vec();
-        let initial_row_re = Regex::new(
-            r"target at y=(-?\d+); min_x=(-?\d+); min_y=(-?\d+); max_x=(-?\d+); max_y=(-?\d+)",
-        )
-        .unwrap();
-        let initial_row = rows.remove(0);
-        let initial_row_match = initial_row_re.captures(initial_row).unwrap();
-        let target_row = initial_row_match[1].parse::<isize>().unwrap();
-        let min_x = initial_row_match[2].parse::<isize>().unwrap();
-        let min_y = initial_row_match[3].parse::<isize>().unwrap();
-        let max_x = initial_row_match[4].parse::<isize>().unwrap();
-        let max_y = initial_row_match[5].parse::<isize>().unwrap();
-
-        let re = Regex::new(
-            r"Sensor at x=(-?\d+), y=(-?\d+): closest beacon is at x=(-?\d+), y=(-?\d+)",
-        )
-        .unwrap();
-
-        let mut output: Vec<(Point, Point)> = Vec::new();
-        for line in rows.iter() {
-            let result = re.captures(line).unwrap();
-
-            let sensor = Point {
-                x: result[1].parse::<isize>().unwrap(),
-                y: result[2].parse::<isize>().unwrap(),
-            };
-            let beacon = Point {
-                x: result[3].parse::<isize>().unwrap(),
-                y: result[4].parse::<isize>().unwrap(),
-            };
-
-            output.push((sensor, beacon));
-        }
-
-        Ok((output, target_row, min_x, min_y, max_x, max_y))
+    fn load(input: &str) -> Result<(Vec<(Pt<2>, Pt<2>)>, isize, isize, isize, isize, isize)> {
+        let (first_line, rest) = input.split_once('\n').unwrap_or((input, ""));
+        let target = Target::from_line(first_line.trim_end())?;
+
+        let output = parse_lines::<Reading>(rest)
+            .map_err(|err| err.shift_lines(1))?
+            .into_iter()
+            .map(|reading| (reading.sensor, reading.beacon))
+            .collect_vec();
+
+        Ok((
+            output,
+            target.row,
+            target.min_x,
+            target.min_y,
+            target.max_x,
+            target.max_y,
+        ))
     }
 
     fn part1(
-        input: &mut (Vec<(Point, Point)>, isize, isize, isize, isize, isize),
+        input: &mut (Vec<(Pt<2>, Pt<2>)>, isize, isize, isize, isize, isize),
     ) -> Result<isize> {
         let (data, target_row, _, _, _, _) = input.clone();
 
-        let resolved_ranges = get_ranges_for_row(&data, target_row).unwrap();
+        let resolved_ranges = get_ranges_for_row(&data, target_row)?;
 
         let mut cells_on_target_row_in_use = HashSet::new();
 
         for (sensor, beacon) in data.clone() {
-            if sensor.y == target_row {
-                cells_on_target_row_in_use.insert(sensor.x);
+            if sensor.0[1] == target_row {
+                cells_on_target_row_in_use.insert(sensor.0[0]);
             }
-            if beacon.y == target_row {
-                cells_on_target_row_in_use.insert(beacon.x);
+            if beacon.0[1] == target_row {
+                cells_on_target_row_in_use.insert(beacon.0[0]);
             }
         }
 
@@ -177,44 +146,63 @@ impl SolutionLinear<(Vec<(Point, Point)>, isize, isize, isize, isize, isize), is
             }
         }
 
-        println!("{}", count);
         Ok(count)
     }
 
     fn part2(
-        input: &mut (Vec<(Point, Point)>, isize, isize, isize, isize, isize),
+        input: &mut (Vec<(Pt<2>, Pt<2>)>, isize, isize, isize, isize, isize),
+        part_1_solution: isize,
+    ) -> Result<isize> {
+        Self::part2_with_progress(input, part_1_solution, &Progress::none())
+    }
+
+    fn part2_with_progress(
+        input: &mut (Vec<(Pt<2>, Pt<2>)>, isize, isize, isize, isize, isize),
         _part_1_solution: isize,
+        progress: &Progress,
     ) -> Result<isize> {
         let (data, _, min_x, min_y, max_x, max_y) = input.clone();
 
+        if let Some(beacon) =
+            find_uncovered(&coverage(&data), Pt([min_x, min_y]), Pt([max_x, max_y]))
+        {
+            return tuning_frequency(beacon);
+        }
+
+        // the beacon is somewhere odd, e.g. along the edge of the box, so go row by row
+        let row_count = (max_y - min_y + 1).max(0) as usize;
+
         for i in min_y..max_y + 1 {
-            let ranges = get_ranges_for_row(&data, i).unwrap();
-            // println!("{:?}", ranges);
-            if ranges.len() == 1 {
-                continue;
+            let rows_scanned = (i - min_y) as usize;
+            progress.update(rows_scanned, row_count, || {
+                format!(
+                    "scanned {} of {} rows (y={}..{}) without finding the beacon",
+                    rows_scanned,
+                    row_count,
+                    min_y,
+                    i - 1
+                )
+            })?;
+
+            // the first x in the box past every range that starts before it
+            let mut x = min_x;
+            for (from, to) in get_ranges_for_row(&data, i)? {
+                if from > x {
+                    break;
+                }
+                x = x.max(to + 1);
             }
-            if ranges.len() == 2 {
-                let y = i;
-                let x = ranges[0].1 + 1;
-                let result = x * 4000000 + y;
-                println!("x: {} y: {} result: {}", x, y, result);
-                return Ok(result.try_into().unwrap());
+            if x <= max_x {
+                return tuning_frequency(Pt([x, i]));
             }
-            panic!();
         }
 
-        Err(anyhow!("Did not arrive at a solution"))
+        Err(anyhow!("Every point in the box is covered by a sensor"))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::Day15Solution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case(
+aoc_examples!(linear Day15Solution;
+    (
         "target at y=10; min_x=0; min_y=0; max_x=20; max_y=20
 Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16
@@ -232,13 +220,83 @@ Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3",
         26,
         56000011
-    )]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: isize, #[case] expected_2: isize) {
-        let mut input = Day15Solution::load(input).unwrap();
-        let p1 = Day15Solution::part1(&mut input).unwrap();
-        let p2 = Day15Solution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
+    ),
+    // the beacon is on the edge of the box, away from any corner or crossing, so only the row
+    // by row search finds it, past a sensor outside the box on the same row
+    (
+        "target at y=7; min_x=0; min_y=0; max_x=7; max_y=7
+Sensor at x=9, y=8: closest beacon is at x=0, y=7
+Sensor at x=-2, y=7: closest beacon is at x=-2, y=-2
+Sensor at x=0, y=-2: closest beacon is at x=2, y=3
+Sensor at x=2, y=-2: closest beacon is at x=3, y=1
+Sensor at x=0, y=8: closest beacon is at x=-2, y=-2
+Sensor at x=20, y=0: closest beacon is at x=20, y=1",
+        28,
+        24000000
+    ),
+);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::get_ranges_for_row;
+    use crate::utils::{
+        point::{Metric, Pt},
+        property::{check, DEFAULT_CASES},
+    };
+    use itertools::Itertools;
+
+    #[test]
+    fn ranges_for_row_match_brute_force() {
+        check(
+            DEFAULT_CASES,
+            15,
+            |rng| {
+                // sensors, with the offset from each sensor to its closest beacon
+                let readings = rng.vec(0, 6, |rng| {
+                    (
+                        (rng.range(-20, 20), rng.range(-20, 20)),
+                        (rng.range(-6, 6), rng.range(-6, 6)),
+                    )
+                });
+                (readings, rng.range(-25, 25))
+            },
+            |(readings, row)| {
+                let row = *row as isize;
+                let data = readings
+                    .iter()
+                    .map(|((x, y), (dx, dy))| {
+                        let sensor = Pt([*x as isize, *y as isize]);
+                        let beacon = Pt([(x + dx) as isize, (y + dy) as isize]);
+                        (sensor, beacon)
+                    })
+                    .collect_vec();
+
+                let ranges = get_ranges_for_row(&data, row).map_err(|err| err.to_string())?;
+
+                if !ranges.iter().tuple_windows().all(|(a, b)| a.1 + 1 < b.0) {
+                    return Err(format!("ranges are not sorted and separate: {:?}", ranges));
+                }
+
+                let covered: BTreeSet<isize> = ranges.iter().flat_map(|(a, b)| *a..=*b).collect();
+                let expected: BTreeSet<isize> = data
+                    .iter()
+                    .flat_map(|(sensor, beacon)| {
+                        let x_dist =
+                            sensor.dist(*beacon, Metric::Manhattan) - (sensor.0[1] - row).abs();
+                        sensor.0[0] - x_dist..=sensor.0[0] + x_dist
+                    })
+                    .collect();
+
+                if covered != expected {
+                    return Err(format!(
+                        "ranges {:?} cover {:?}, expected {:?}",
+                        ranges, covered, expected
+                    ));
+                }
+                Ok(())
+            },
+        );
     }
 }
@@ -18,22 +18,190 @@ pub mod templates;
 
 use anyhow::Result;
 
-/// Add new solutions to this const
-pub const SOLUTIONS: [fn(&str) -> Result<f32>; 16] = [
-    day01::day01,
-    day02::day02,
-    day03::day03,
-    day04::day04,
-    day05::day05,
-    day06::day06,
-    day07::day07,
-    day08::day08,
-    day09::day09,
-    day10::day10,
-    day11::day11,
-    day12::day12,
-    day13::day13,
-    day14::day14,
-    day15::day15,
-    day16::day16,
+use crate::utils::{examples::Example, simulation::Simulation, solver_types::Solved};
+
+/// A named implementation of a day's solution
+pub type Implementation = (&'static str, fn(&str) -> Result<Solved>);
+
+/// Add new solutions to this const.
+/// A day can have several implementations, selected with --impl. The first is the default
+pub const SOLUTIONS: [&[Implementation]; 16] = [
+    &[("default", day01::day01)],
+    &[("default", day02::day02)],
+    &[("default", day03::day03)],
+    &[("default", day04::day04)],
+    &[("default", day05::day05)],
+    &[("naive", day06::day06), ("fast", day06::day06_fast)],
+    &[("default", day07::day07)],
+    &[("default", day08::day08)],
+    &[("default", day09::day09)],
+    &[("default", day10::day10)],
+    &[("default", day11::day11)],
+    &[("default", day12::day12)],
+    &[("default", day13::day13)],
+    &[("default", day14::day14)],
+    &[("default", day15::day15)],
+    &[("default", day16::day16)],
+];
+
+/// Builds a day's simulation from its input, for the given part
+pub type SimulationBuilder = fn(&str, usize) -> Result<Box<dyn Simulation>>;
+
+/// Days that are solved by stepping a `Simulation`, keyed by day number.
+/// Add a day here so it can be stepped through with --step
+pub const SIMULATIONS: [(usize, SimulationBuilder); 4] = [
+    (9, day09::simulation),
+    (10, day10::simulation),
+    (11, day11::simulation),
+    (14, day14::simulation),
 ];
+
+/// Each day's worked examples, declared with `aoc_examples!`. Run them with --mode example --day N
+pub const EXAMPLES: [&[Example]; 16] = [
+    day01::EXAMPLES,
+    day02::EXAMPLES,
+    day03::EXAMPLES,
+    day04::EXAMPLES,
+    day05::EXAMPLES,
+    day06::EXAMPLES,
+    day07::EXAMPLES,
+    day08::EXAMPLES,
+    day09::EXAMPLES,
+    day10::EXAMPLES,
+    day11::EXAMPLES,
+    day12::EXAMPLES,
+    day13::EXAMPLES,
+    day14::EXAMPLES,
+    day15::EXAMPLES,
+    day16::EXAMPLES,
+];
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::utils::property::{check, Rng, DEFAULT_CASES};
+
+    type Loader = fn(&str) -> Result<()>;
+
+    /// Each day's loader, along with its example inputs to mutate
+    const LOADERS: [(Loader, &[&str]); 16] = [
+        (day01::load_only, day01::EXAMPLE_INPUTS),
+        (day02::load_only, day02::EXAMPLE_INPUTS),
+        (day03::load_only, day03::EXAMPLE_INPUTS),
+        (day04::load_only, day04::EXAMPLE_INPUTS),
+        (day05::load_only, day05::EXAMPLE_INPUTS),
+        (day06::load_only, day06::EXAMPLE_INPUTS),
+        (day07::load_only, day07::EXAMPLE_INPUTS),
+        (day08::load_only, day08::EXAMPLE_INPUTS),
+        (day09::load_only, day09::EXAMPLE_INPUTS),
+        (day10::load_only, day10::EXAMPLE_INPUTS),
+        (day11::load_only, day11::EXAMPLE_INPUTS),
+        (day12::load_only, day12::EXAMPLE_INPUTS),
+        (day13::load_only, day13::EXAMPLE_INPUTS),
+        (day14::load_only, day14::EXAMPLE_INPUTS),
+        (day15::load_only, day15::EXAMPLE_INPUTS),
+        (day16::load_only, day16::EXAMPLE_INPUTS),
+    ];
+
+    /// characters that show up in puzzle inputs, so mutations are more likely to get past the first line
+    const ALPHABET: &[char] = &[
+        '0', '1', '9', '-', '+', '*', ' ', ',', ':', ';', '=', '\n', '[', ']', '$', '/', '.',
+    ];
+
+    fn random_char(rng: &mut Rng, example: &[char]) -> char {
+        match rng.below(3) {
+            0 if !example.is_empty() => example[rng.below(example.len())],
+            1 => ALPHABET[rng.below(ALPHABET.len())],
+            _ => char::from_u32(rng.range(0, 0x24f) as u32).unwrap_or('?'),
+        }
+    }
+
+    /// random text, a truncated example, or an example with a few characters changed
+    fn generate_input(rng: &mut Rng, examples: &[&str]) -> Vec<char> {
+        let example = examples[rng.below(examples.len())]
+            .chars()
+            .collect::<Vec<_>>();
+
+        match rng.below(3) {
+            0 => rng.vec(0, 100, |rng| random_char(rng, &example)),
+            1 => example[..rng.below(example.len() + 1)].to_vec(),
+            _ => {
+                let mut mutated = example.clone();
+                for _ in 0..rng.range(1, 4) {
+                    let at = rng.below(mutated.len() + 1);
+                    match rng.below(3) {
+                        0 if at < mutated.len() => {
+                            mutated.remove(at);
+                        }
+                        1 if at < mutated.len() => mutated[at] = random_char(rng, &example),
+                        _ => mutated.insert(at, random_char(rng, &example)),
+                    }
+                }
+                mutated
+            }
+        }
+    }
+
+    /// every implementation of a day gets the same answers to its worked examples as the default,
+    /// which `validate_examples` checks against the puzzle
+    #[test]
+    fn implementations_agree_on_examples() {
+        for (day, implementations) in SOLUTIONS.iter().enumerate() {
+            let ((_, default), others) = match implementations.split_first() {
+                Some(split) if !split.1.is_empty() => split,
+                _ => continue,
+            };
+            for input in LOADERS[day].1 {
+                let expected = default(input).unwrap();
+                for (name, solve) in others {
+                    let solved = solve(input).unwrap();
+                    assert_eq!(
+                        (&expected.part1, &expected.part2),
+                        (&solved.part1, &solved.part2),
+                        "day {} {} on {:?}",
+                        day + 1,
+                        name,
+                        input
+                    );
+                }
+            }
+        }
+    }
+
+    /// inputs that load without panicking but used to make a part panic or never finish
+    #[rstest]
+    #[case::too_short_for_a_message(6, "abcdefg")]
+    #[case::rock_above_the_sand_source(14, "498,-10 -> 502,-10")]
+    #[case::rock_at_the_most_negative_x(14, "-9223372036854775808,0 -> 0,0")]
+    fn bad_inputs_are_rejected(#[case] day: usize, #[case] input: &str) {
+        for (name, solve) in SOLUTIONS[day - 1] {
+            assert!(solve(input).is_err(), "{} accepted {:?}", name, input);
+        }
+    }
+
+    #[test]
+    fn deeply_nested_packets_are_rejected() {
+        let packet = "[".repeat(200000) + &"]".repeat(200000);
+        let input = format!("{}\n[]\n", packet);
+        assert!(day13::load_only(&input).is_err());
+    }
+
+    #[test]
+    fn loaders_never_panic() {
+        for (day, (load, examples)) in LOADERS.iter().enumerate() {
+            check(
+                DEFAULT_CASES,
+                day as u64 + 1,
+                |rng| generate_input(rng, examples),
+                |input| {
+                    // errors are fine, only panics fail
+                    let _ = load(&input.iter().collect::<String>());
+                    Ok(())
+                },
+            );
+        }
+    }
+}
@@ -1,11 +1,12 @@
 
-use crate::utils::solver_types::{solve_linear, SolutionLinear};
+use crate::aoc_examples;
+use crate::utils::solver_types::{solve_linear, SolutionLinear, Solved};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct TemplateSolution {}
 
-pub fn template(input: &str) -> Result<f32> {
+pub fn template(input: &str) -> Result<Solved> {
     solve_linear::<TemplateSolution, _, _, _>(input)
 }
 
@@ -23,20 +24,6 @@ impl SolutionLinear<_, _, _> for TemplateSolution {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::TemplateSolution;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case("", 0, 0)]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: _, #[case] expected_2: _) {
-        let mut input = TemplateSolution::load(input).unwrap();
-        let p1 = TemplateSolution::part1(&mut input).unwrap();
-        let p2 = TemplateSolution::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+aoc_examples!(linear TemplateSolution;
+    ("", 0, 0),
+);
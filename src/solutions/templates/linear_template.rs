@@ -1,3 +1,4 @@
+use crate::aoc_examples;
 use crate::utils::solver_types::SolutionLinear;
 use anyhow::Result;
 
@@ -26,22 +27,8 @@ impl SolutionLinear<Vec<usize>, usize, usize> for ExampleSolutionLinear {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::ExampleSolutionLinear;
-    use crate::utils::solver_types::SolutionLinear;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case("[1,2,3]", 6, 18)]
-    #[case("[0,7,13,20,1,100]", 141, 846)]
-    #[case("[6000]", 6000, 6000)]
-    fn validate_linear(#[case] input: &str, #[case] expected_1: usize, #[case] expected_2: usize) {
-        let mut input = ExampleSolutionLinear::load(input).unwrap();
-        let p1 = ExampleSolutionLinear::part1(&mut input).unwrap();
-        let p2 = ExampleSolutionLinear::part2(&mut input, p1).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+aoc_examples!(linear ExampleSolutionLinear;
+    ("[1,2,3]", 6, 18),
+    ("[0,7,13,20,1,100]", 141, 846),
+    ("[6000]", 6000, 6000),
+);
@@ -1,3 +1,4 @@
+use crate::aoc_examples;
 use crate::utils::solver_types::SolutionSimultaneous;
 use anyhow::anyhow;
 use itertools::Itertools;
@@ -32,21 +33,8 @@ impl SolutionSimultaneous<Vec<usize>, usize, usize> for ExampleSolutionSimultane
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::ExampleSolutionSimultaneous;
-    use crate::utils::solver_types::SolutionSimultaneous;
-    use rstest::rstest;
-
-    #[rstest]
-    #[case("[6,5,4,2,3,5,8]", 3, 5)]
-    #[case("[1,2,6,4,100]", 2, 6)]
-    #[case("[5,4,3,1,2,7]", 2, 7)]
-    fn validate_simul(#[case] input: &str, #[case] expected_1: usize, #[case] expected_2: usize) {
-        let input = ExampleSolutionSimultaneous::load(input).unwrap();
-        let (p1, p2) = ExampleSolutionSimultaneous::solve(input).unwrap();
-
-        assert_eq!(expected_1, p1);
-        assert_eq!(expected_2, p2);
-    }
-}
+aoc_examples!(simultaneous ExampleSolutionSimultaneous;
+    ("[6,5,4,2,3,5,8]", 3, 5),
+    ("[1,2,6,4,100]", 2, 6),
+    ("[5,4,3,1,2,7]", 2, 7),
+);
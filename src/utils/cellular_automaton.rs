@@ -0,0 +1,366 @@
+//! Conway-style cellular automata: every point in a `Grid` changes at once, based on its own value
+//! and the values of its neighbours.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use itertools::Itertools;
+use num::iter::range_inclusive;
+
+use super::{
+    grid::{Adjacency, Grid},
+    point::{Pt, Scalar},
+};
+
+/// the min and max values of each dimension, like `Grid::bounds`
+type Bounds<const DIMS: usize, S> = ([S; DIMS], [S; DIMS]);
+
+/// gives a point's next value from its value and its neighbours' values
+type Rule<T> = Box<dyn Fn(T, &[T]) -> T>;
+
+/// A state that keeps coming back, found by `CellularAutomaton::step`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// the first generation in the cycle
+    pub start: usize,
+    /// how many generations until it comes back round, where 1 means it's stopped changing
+    pub length: usize,
+}
+
+impl Cycle {
+    /// whether the automaton has stopped changing
+    pub fn is_fixed_point(&self) -> bool {
+        self.length == 1
+    }
+}
+
+/// Steps a grid of values of type `T` using a rule that's given a point's value and its
+/// neighbours' values, in no particular order, and returns its value in the next generation.
+///
+/// Points with the grid's default value aren't stored, so the grid can grow forever. If the rule
+/// changes a point surrounded by default values (e.g. a B0 rule in Life terms), every point that
+/// isn't stored changes with it, so an infinite automaton changes its grid's default instead
+pub struct CellularAutomaton<T: Copy, const DIMS: usize, S: Scalar = isize> {
+    grid: Grid<T, DIMS, S>,
+    /// Moore (`Full`) or von Neumann (`Cardinal`)
+    neighbourhood: Adjacency,
+    /// the only points that can change, or `None` to grow without limit
+    bounds: Option<Bounds<DIMS, S>>,
+    rule: Rule<T>,
+    generation: usize,
+    /// the grid at generation 0, to replay up to an earlier state whose hash matches
+    initial: Grid<T, DIMS, S>,
+    /// the generations each state's hash was seen in, until a cycle is found
+    seen: HashMap<u64, Vec<usize>>,
+    cycle: Option<Cycle>,
+}
+
+#[allow(dead_code)]
+impl<T: Copy + Eq + Hash, const DIMS: usize, S: Scalar> CellularAutomaton<T, DIMS, S> {
+    /// an automaton that grows forever, with points beyond the grid starting as its default value
+    pub fn infinite(
+        grid: Grid<T, DIMS, S>,
+        neighbourhood: Adjacency,
+        rule: impl Fn(T, &[T]) -> T + 'static,
+    ) -> Self {
+        Self::new(grid, neighbourhood, None, Box::new(rule))
+    }
+
+    /// an automaton where only the points inside `bounds` change. Points outside count as the
+    /// grid's default value, and any stored there are dropped
+    pub fn bounded(
+        grid: Grid<T, DIMS, S>,
+        neighbourhood: Adjacency,
+        bounds: ([S; DIMS], [S; DIMS]),
+        rule: impl Fn(T, &[T]) -> T + 'static,
+    ) -> Self {
+        Self::new(grid, neighbourhood, Some(bounds), Box::new(rule))
+    }
+
+    fn new(
+        mut grid: Grid<T, DIMS, S>,
+        neighbourhood: Adjacency,
+        bounds: Option<Bounds<DIMS, S>>,
+        rule: Rule<T>,
+    ) -> Self {
+        let default = grid.default_value();
+        grid.grid.retain(|pt, value| {
+            *value != default && bounds.map_or(true, |bounds| in_bounds(pt, bounds))
+        });
+
+        Self {
+            seen: HashMap::from([(state_hash(&grid), vec![0])]),
+            initial: grid.clone(),
+            grid,
+            neighbourhood,
+            bounds,
+            rule,
+            generation: 0,
+            cycle: None,
+        }
+    }
+
+    /// the current generation, starting from 0
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn grid(&self) -> &Grid<T, DIMS, S> {
+        &self.grid
+    }
+
+    /// the cycle the automaton has fallen into, once a state has come back
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    /// the generation after `grid`
+    fn next(&self, grid: &Grid<T, DIMS, S>) -> Grid<T, DIMS, S> {
+        let candidates: HashSet<Pt<DIMS, S>> = match self.bounds {
+            Some(bounds) => points_in(bounds).collect(),
+            None => grid
+                .grid
+                .keys()
+                .flat_map(|pt| grid.neighbours(pt).chain([*pt]))
+                .collect(),
+        };
+
+        // outside the bounds stays the same, but everywhere else follows the rule
+        let mut default = grid.default_value();
+        if self.bounds.is_none() {
+            let neighbours = vec![default; self.neighbourhood.offsets::<DIMS, S>().len()];
+            default = (self.rule)(default, &neighbours);
+        }
+
+        let mut next = grid.clone();
+        next.set_default(default);
+        next.grid = candidates
+            .into_iter()
+            .filter_map(|pt| {
+                let neighbours = grid
+                    .adjacent_values(&pt, self.neighbourhood)
+                    .map(|(_, value)| value)
+                    .collect_vec();
+                let value = (self.rule)(grid.get_def(&pt), &neighbours);
+                (value != default).then_some((pt, value))
+            })
+            .collect();
+        next
+    }
+
+    /// the grid as it was at an earlier `generation`
+    fn replay(&self, generation: usize) -> Grid<T, DIMS, S> {
+        (0..generation).fold(self.initial.clone(), |grid, _| self.next(&grid))
+    }
+
+    /// advance every point by one generation at once
+    pub fn step(&mut self) {
+        self.grid = self.next(&self.grid);
+        self.generation += 1;
+
+        if self.cycle.is_some() {
+            return;
+        }
+
+        let hash = state_hash(&self.grid);
+        // usually the same state, but it could be a different one with the same hash
+        let start = self.seen.get(&hash).and_then(|generations| {
+            generations
+                .iter()
+                .copied()
+                .find(|earlier| self.replay(*earlier) == self.grid)
+        });
+        match start {
+            Some(start) => {
+                self.cycle = Some(Cycle {
+                    start,
+                    length: self.generation - start,
+                });
+                // nothing more to find
+                self.seen.clear();
+            }
+            None => self.seen.entry(hash).or_default().push(self.generation),
+        }
+    }
+
+    /// step until a cycle is found or `max_generations` is reached, calling `on_generation` after
+    /// each step, e.g. to print it
+    pub fn run(
+        &mut self,
+        max_generations: usize,
+        mut on_generation: impl FnMut(&Self),
+    ) -> Option<Cycle> {
+        while self.cycle.is_none() && self.generation < max_generations {
+            self.step();
+            on_generation(self);
+        }
+        self.cycle
+    }
+}
+
+/// a hash of the grid's points and default value, whatever order the points are stored in
+fn state_hash<T: Copy + Hash, const DIMS: usize, S: Scalar>(grid: &Grid<T, DIMS, S>) -> u64 {
+    fn hash_of(value: impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    grid.grid
+        .iter()
+        .fold(hash_of(grid.default_value()), |sum, entry| {
+            sum.wrapping_add(hash_of(entry))
+        })
+}
+
+fn in_bounds<const DIMS: usize, S: Scalar>(
+    pt: &Pt<DIMS, S>,
+    (mins, maxs): ([S; DIMS], [S; DIMS]),
+) -> bool {
+    (0..DIMS).all(|i| mins[i] <= pt.0[i] && pt.0[i] <= maxs[i])
+}
+
+/// every point from `mins` to `maxs` inclusive
+fn points_in<const DIMS: usize, S: Scalar>(
+    (mins, maxs): ([S; DIMS], [S; DIMS]),
+) -> impl Iterator<Item = Pt<DIMS, S>> {
+    (0..DIMS)
+        .map(|i| range_inclusive(mins[i], maxs[i]))
+        .multi_cartesian_product()
+        .map(|coords| Pt::try_from(coords).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::{CellularAutomaton, Cycle};
+    use crate::utils::{
+        grid::{Adjacency, Grid},
+        point::Pt,
+    };
+
+    fn life(alive: bool, neighbours: &[bool]) -> bool {
+        let count = neighbours.iter().filter(|n| **n).count();
+        count == 3 || alive && count == 2
+    }
+
+    fn pattern(input: &str) -> Grid<bool, 2> {
+        Grid::from_text(input, |c| c == '#')
+    }
+
+    fn alive<const DIMS: usize>(automaton: &CellularAutomaton<bool, DIMS>) -> Vec<Pt<DIMS>> {
+        automaton.grid().grid.keys().copied().sorted().collect_vec()
+    }
+
+    #[test]
+    fn test_oscillator_and_still_life() {
+        let mut blinker = CellularAutomaton::infinite(pattern("###"), Adjacency::Full, life);
+        assert_eq!(
+            Some(Cycle {
+                start: 0,
+                length: 2
+            }),
+            blinker.run(10, |_| {})
+        );
+        assert_eq!(2, blinker.generation());
+
+        let mut block = CellularAutomaton::infinite(pattern("##\n##"), Adjacency::Full, life);
+        let cycle = block.run(10, |_| {}).unwrap();
+        assert!(cycle.is_fixed_point());
+        assert_eq!(1, block.generation());
+    }
+
+    #[test]
+    fn test_glider() {
+        let glider = pattern(".#.\n..#\n###");
+        let start = alive(&CellularAutomaton::infinite(
+            glider.clone(),
+            Adjacency::Full,
+            life,
+        ));
+
+        let mut automaton = CellularAutomaton::infinite(glider.clone(), Adjacency::Full, life);
+        let mut printed = vec![];
+        automaton.run(4, |automaton| {
+            printed.push(
+                automaton
+                    .grid()
+                    .print(|alive| if alive { '#' } else { '.' }),
+            )
+        });
+        assert_eq!(4, printed.len());
+        assert_eq!(None, automaton.cycle());
+        assert_eq!(
+            start.iter().map(|pt| *pt + Pt([1, 1])).collect_vec(),
+            alive(&automaton)
+        );
+
+        // stuck in the corner of a box it turns into a block
+        let mut boxed = CellularAutomaton::bounded(glider, Adjacency::Full, ([0, 0], [3, 3]), life);
+        assert!(boxed.run(20, |_| {}).unwrap().is_fixed_point());
+        assert_eq!(
+            vec![Pt([2, 2]), Pt([2, 3]), Pt([3, 2]), Pt([3, 3])],
+            alive(&boxed)
+        );
+    }
+
+    #[test]
+    fn test_b0_changes_the_default() {
+        // every empty point comes alive, and every live one dies
+        let strobe = |alive: bool, _: &[bool]| !alive;
+
+        let mut infinite = CellularAutomaton::infinite(pattern("#"), Adjacency::Full, strobe);
+        infinite.step();
+        assert!(infinite.grid().default_value());
+        assert!(infinite.grid().get_def(&Pt([100, -100])));
+        assert!(!infinite.grid().get_def(&Pt([0, 0])));
+        assert_eq!(1, infinite.grid().grid.len());
+        assert_eq!(
+            Some(Cycle {
+                start: 0,
+                length: 2
+            }),
+            infinite.run(10, |_| {})
+        );
+
+        // outside the bounds never changes
+        let mut bounded =
+            CellularAutomaton::bounded(pattern("#"), Adjacency::Full, ([0, 0], [1, 1]), strobe);
+        bounded.step();
+        assert!(!bounded.grid().default_value());
+        assert_eq!(vec![Pt([0, 1]), Pt([1, 0]), Pt([1, 1])], alive(&bounded));
+    }
+
+    #[test]
+    fn test_von_neumann_growth() {
+        let seed = Grid::<bool, 2>::from(vec![(Pt([0, 0]), true)]);
+        let mut automaton = CellularAutomaton::infinite(seed, Adjacency::Cardinal, |alive, ns| {
+            alive || ns.contains(&true)
+        });
+        automaton.run(3, |_| {});
+        // a diamond of radius 3
+        assert_eq!(25, automaton.grid().grid.len());
+    }
+
+    #[test]
+    fn test_3d() {
+        let mut cubes = Grid::<bool, 3>::default();
+        for (pt, _) in pattern(".#.\n..#\n###")
+            .grid
+            .iter()
+            .filter(|(_, alive)| **alive)
+        {
+            cubes.grid.insert(Pt([pt.0[0], pt.0[1], 0]), true);
+        }
+
+        let mut automaton = CellularAutomaton::infinite(cubes, Adjacency::Full, |active, ns| {
+            let count = ns.iter().filter(|n| **n).count();
+            count == 3 || active && count == 2
+        });
+        automaton.run(6, |_| {});
+        assert_eq!(112, automaton.grid().grid.len());
+    }
+}
@@ -0,0 +1,77 @@
+//! Arithmetic for values that could overflow on a large enough input.
+//! With the `checked` feature (`cargo run --features checked -- ...`) overflow is an error that says
+//! what was being calculated. Without it these are the plain operators, so they cost nothing,
+//! panic on overflow in debug builds, and wrap silently in release builds.
+
+use std::fmt::Display;
+
+use anyhow::Result;
+use num::{CheckedAdd, CheckedMul, CheckedSub};
+
+#[cfg(feature = "checked")]
+fn overflow<T: Display>(a: T, op: &str, b: T, context: &str) -> anyhow::Error {
+    anyhow::anyhow!("Overflow calculating {}: {} {} {}", context, a, op, b)
+}
+
+/// `a + b`, where `context` describes what's being calculated
+#[cfg(feature = "checked")]
+pub fn add<T: CheckedAdd + Display>(a: T, b: T, context: &str) -> Result<T> {
+    a.checked_add(&b)
+        .ok_or_else(|| overflow(a, "+", b, context))
+}
+
+/// `a - b`, where `context` describes what's being calculated
+#[cfg(feature = "checked")]
+pub fn sub<T: CheckedSub + Display>(a: T, b: T, context: &str) -> Result<T> {
+    a.checked_sub(&b)
+        .ok_or_else(|| overflow(a, "-", b, context))
+}
+
+/// `a * b`, where `context` describes what's being calculated
+#[cfg(feature = "checked")]
+pub fn mul<T: CheckedMul + Display>(a: T, b: T, context: &str) -> Result<T> {
+    a.checked_mul(&b)
+        .ok_or_else(|| overflow(a, "*", b, context))
+}
+
+/// `a + b`, where `context` describes what's being calculated
+#[cfg(not(feature = "checked"))]
+pub fn add<T: CheckedAdd + Display>(a: T, b: T, _context: &str) -> Result<T> {
+    Ok(a + b)
+}
+
+/// `a - b`, where `context` describes what's being calculated
+#[cfg(not(feature = "checked"))]
+pub fn sub<T: CheckedSub + Display>(a: T, b: T, _context: &str) -> Result<T> {
+    Ok(a - b)
+}
+
+/// `a * b`, where `context` describes what's being calculated
+#[cfg(not(feature = "checked"))]
+pub fn mul<T: CheckedMul + Display>(a: T, b: T, _context: &str) -> Result<T> {
+    Ok(a * b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add, mul, sub};
+
+    #[test]
+    fn test_in_range() {
+        assert_eq!(7, add(3, 4, "a sum").unwrap());
+        assert_eq!(-1, sub(3, 4, "a difference").unwrap());
+        assert_eq!(12_i64, mul(3, 4, "a product").unwrap());
+    }
+
+    #[cfg(feature = "checked")]
+    #[test]
+    fn test_overflow_is_an_error() {
+        let err = mul(i32::MAX, 2, "the tuning frequency").unwrap_err();
+        assert_eq!(
+            "Overflow calculating the tuning frequency: 2147483647 * 2",
+            err.to_string()
+        );
+        assert!(add(u8::MAX, 1, "a sum").is_err());
+        assert!(sub(0_usize, 1, "a difference").is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! A grid with fixed bounds that stores every cell in one `Vec`, for puzzles where the whole
+//! rectangle (or box) matters, like a height map. Much faster than the `HashMap` in `Grid`,
+//! but points outside the bounds can't be stored.
+
+use std::array;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use num::{iter::range_inclusive, NumCast};
+
+use super::{
+    grid::GridLike,
+    point::{Pt, Scalar},
+};
+
+/// Values of type `T` at every point from `min` to `max`, with coordinates of type `S`.
+/// Stored with the first dimension varying fastest, so a 2D grid is stored row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenseGrid<T: Copy, const DIMS: usize, S: Scalar = isize> {
+    min: Pt<DIMS, S>,
+    /// the number of points along each dimension
+    size: [usize; DIMS],
+    cells: Vec<T>,
+    /// neighbour offsets, worked out once and in a fixed order so ties are broken the same way
+    offsets: Vec<Pt<DIMS, S>>,
+    /// cardinal offsets, in a fixed order
+    card_offsets: Vec<Pt<DIMS, S>>,
+}
+
+#[allow(dead_code)]
+impl<T: Copy, const DIMS: usize, S: Scalar> DenseGrid<T, DIMS, S> {
+    /// a grid covering `min` to `max` inclusive, with every point set to `fill`
+    pub fn new(min: Pt<DIMS, S>, max: Pt<DIMS, S>, fill: T) -> Self {
+        let size = array::from_fn(|i| (max.0[i] - min.0[i]).to_usize().map_or(0, |len| len + 1));
+        Self {
+            min,
+            size,
+            cells: vec![fill; size.iter().product()],
+            offsets: Pt::neighbour_offsets().into_iter().sorted().collect(),
+            card_offsets: Pt::card_offsets().into_iter().sorted().collect(),
+        }
+    }
+
+    /// the number of points along each dimension
+    pub fn size(&self) -> [usize; DIMS] {
+        self.size
+    }
+
+    /// where `pt` is stored in `cells`, if it's inside the grid
+    fn index(&self, pt: &Pt<DIMS, S>) -> Option<usize> {
+        let mut index = 0;
+        for i in (0..DIMS).rev() {
+            let offset = (pt.0[i] - self.min.0[i]).to_usize()?;
+            if offset >= self.size[i] {
+                return None;
+            }
+            index = index * self.size[i] + offset;
+        }
+        Some(index)
+    }
+
+    /// the point stored at `index` in `cells`
+    fn point(&self, mut index: usize) -> Pt<DIMS, S> {
+        let mut pt = self.min;
+        for i in 0..DIMS {
+            pt.0[i] += <S as NumCast>::from(index % self.size[i]).unwrap();
+            index /= self.size[i];
+        }
+        pt
+    }
+
+    /// the points at each of `offsets` from `pt` that are inside the grid, with their values
+    fn values_at(&self, pt: &Pt<DIMS, S>, offsets: &[Pt<DIMS, S>]) -> Vec<(Pt<DIMS, S>, T)> {
+        offsets
+            .iter()
+            .filter_map(|offset| {
+                let neighbour = *pt + *offset;
+                self.get(&neighbour).map(|value| (neighbour, value))
+            })
+            .collect()
+    }
+
+    /// the value at `pt` to change in place, or `None` if it's outside the grid
+    pub fn get_mut(&mut self, pt: &Pt<DIMS, S>) -> Option<&mut T> {
+        let index = self.index(pt)?;
+        Some(&mut self.cells[index])
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Copy> DenseGrid<T, 2> {
+    /// a grid from rows of values, with the first row at `y = 0` and each row going left to
+    /// right from `x = 0`. Fails if the rows aren't all the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            return Err(anyhow!(
+                "Row {} has {} values, but the first row has {}",
+                y,
+                rows[y].len(),
+                width
+            ));
+        }
+
+        Ok(Self {
+            min: Pt::default(),
+            size: [width, rows.len()],
+            cells: rows.into_iter().flatten().collect(),
+            offsets: Pt::neighbour_offsets().into_iter().sorted().collect(),
+            card_offsets: Pt::card_offsets().into_iter().sorted().collect(),
+        })
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Copy, S: Scalar> DenseGrid<T, 2, S> {
+    /// print a 2d grid using a given function for representing points
+    pub fn print(&self, to_printable: fn(T) -> char) -> String {
+        let mut res = String::from("\n");
+
+        let ([min_x, min_y], [max_x, max_y]) = self.bounds();
+
+        for y in range_inclusive(min_y, max_y) {
+            for x in range_inclusive(min_x, max_x) {
+                res.extend(self.get(&Pt([x, y])).map(to_printable));
+            }
+            res.push('\n');
+        }
+
+        res
+    }
+}
+
+impl<T: Copy, const DIMS: usize, S: Scalar> GridLike<T, DIMS, S> for DenseGrid<T, DIMS, S> {
+    fn get(&self, pt: &Pt<DIMS, S>) -> Option<T> {
+        self.index(pt).map(|index| self.cells[index])
+    }
+
+    fn set(&mut self, pt: Pt<DIMS, S>, value: T) -> Result<()> {
+        let (min, max) = self.bounds();
+        let cell = self.get_mut(&pt).ok_or_else(|| {
+            anyhow!(
+                "{:?} is outside the grid, which goes from {:?} to {:?}",
+                pt.0,
+                min,
+                max
+            )
+        })?;
+        *cell = value;
+        Ok(())
+    }
+
+    fn bounds(&self) -> ([S; DIMS], [S; DIMS]) {
+        let max = array::from_fn(|i| {
+            self.min.0[i] + <S as NumCast>::from(self.size[i]).unwrap() - S::one()
+        });
+        (self.min.0, max)
+    }
+
+    fn cells(&self) -> Box<dyn Iterator<Item = (Pt<DIMS, S>, T)> + '_> {
+        Box::new(
+            self.cells
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (self.point(index), *value)),
+        )
+    }
+
+    fn neighbour_values(&self, pt: &Pt<DIMS, S>) -> Vec<(Pt<DIMS, S>, T)> {
+        self.values_at(pt, &self.offsets)
+    }
+
+    fn card_neighbour_values(&self, pt: &Pt<DIMS, S>) -> Vec<(Pt<DIMS, S>, T)> {
+        self.values_at(pt, &self.card_offsets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::DenseGrid;
+    use crate::utils::{
+        grid::{Grid, GridLike},
+        point::Pt,
+    };
+
+    #[test]
+    fn test_from_rows() {
+        let grid = DenseGrid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+
+        assert_eq!([3, 2], grid.size());
+        assert_eq!(([0, 0], [2, 1]), grid.bounds());
+        assert_eq!(Some(6), grid.get(&Pt([2, 1])));
+        assert_eq!(None, grid.get(&Pt([3, 1])));
+        assert_eq!(None, grid.get(&Pt([0, -1])));
+        assert_eq!(
+            "\n123\n456\n",
+            grid.print(|x| char::from_digit(x, 10).unwrap())
+        );
+
+        assert!(DenseGrid::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+    }
+
+    #[test]
+    fn test_set_and_cells() {
+        let mut grid = DenseGrid::<char, 3, i16>::new(Pt([-1, -1, -1]), Pt([1, 1, 1]), '.');
+        assert_eq!(27, grid.cells().count());
+
+        grid.set(Pt([1, -1, 0]), '#').unwrap();
+        *grid.get_mut(&Pt([-1, 1, 1])).unwrap() = '@';
+        assert!(grid.set(Pt([2, 0, 0]), '#').is_err());
+
+        let marked = grid
+            .cells()
+            .filter(|(_, value)| *value != '.')
+            .sorted()
+            .collect_vec();
+        assert_eq!(vec![(Pt([-1, 1, 1]), '@'), (Pt([1, -1, 0]), '#')], marked);
+    }
+
+    /// how many cells have a cardinal neighbour with the same value, written once for either grid
+    fn count_next_to_equal<G: GridLike<u32, 2>>(grid: &G) -> usize {
+        grid.cells()
+            .filter(|(pt, value)| {
+                grid.card_neighbour_values(pt)
+                    .iter()
+                    .any(|(_, other)| other == value)
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_same_algorithm_on_either_backend() {
+        let rows = vec![vec![1, 1, 2], vec![3, 4, 2], vec![5, 4, 6]];
+        let dense = DenseGrid::from_rows(rows).unwrap();
+        let mut sparse = Grid::<u32, 2>::default();
+        sparse.set_default(u32::MAX);
+        for (pt, value) in dense.cells() {
+            sparse.set(pt, value).unwrap();
+        }
+
+        assert_eq!(6, count_next_to_equal(&dense));
+        assert_eq!(6, count_next_to_equal(&sparse));
+        assert_eq!(dense.bounds(), GridLike::bounds(&sparse));
+        assert_eq!(3, dense.neighbour_values(&Pt([0, 0])).len());
+        assert_eq!(8, dense.neighbour_values(&Pt([1, 1])).len());
+        // always in the same order, so algorithms break ties the same way every run
+        assert_eq!(
+            vec![(Pt([0, 1]), 3), (Pt([1, 0]), 1)],
+            dense.card_neighbour_values(&Pt([0, 0]))
+        );
+        assert_eq!(
+            vec![(Pt([0, 1]), 3), (Pt([1, 0]), 1), (Pt([1, 1]), 4)],
+            dense.neighbour_values(&Pt([0, 0]))
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Compass directions on a 2D grid. y points down, like in `Grid::print`, so `Up` is `Pt([0, -1])`.
+
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Error, Result};
+
+use super::point::Pt;
+
+/// One of the four cardinal directions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+/// One of the eight directions to a neighbouring square, including diagonals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+#[allow(dead_code)]
+impl Dir4 {
+    /// every direction, clockwise from up
+    pub const ALL: [Dir4; 4] = [Dir4::Up, Dir4::Right, Dir4::Down, Dir4::Left];
+
+    /// one square in this direction
+    pub fn offset(self) -> Pt<2> {
+        match self {
+            Dir4::Up => Pt([0, -1]),
+            Dir4::Right => Pt([1, 0]),
+            Dir4::Down => Pt([0, 1]),
+            Dir4::Left => Pt([-1, 0]),
+        }
+    }
+
+    /// the direction `quarter_turns` quarter turns clockwise from this one. Negative turns go anticlockwise
+    pub fn turn(self, quarter_turns: isize) -> Dir4 {
+        Dir4::ALL[(self as isize + quarter_turns).rem_euclid(4) as usize]
+    }
+
+    pub fn turn_right(self) -> Dir4 {
+        self.turn(1)
+    }
+
+    pub fn turn_left(self) -> Dir4 {
+        self.turn(-1)
+    }
+
+    pub fn turn_around(self) -> Dir4 {
+        self.turn(2)
+    }
+
+    /// the direction a step of `offset` goes in, if it's a single square along an axis
+    pub fn from_offset(offset: Pt<2>) -> Option<Dir4> {
+        Dir4::ALL.into_iter().find(|dir| dir.offset() == offset)
+    }
+}
+
+#[allow(dead_code)]
+impl Dir8 {
+    /// every direction, clockwise from up
+    pub const ALL: [Dir8; 8] = [
+        Dir8::Up,
+        Dir8::UpRight,
+        Dir8::Right,
+        Dir8::DownRight,
+        Dir8::Down,
+        Dir8::DownLeft,
+        Dir8::Left,
+        Dir8::UpLeft,
+    ];
+
+    /// one square in this direction
+    pub fn offset(self) -> Pt<2> {
+        match self {
+            Dir8::Up => Pt([0, -1]),
+            Dir8::UpRight => Pt([1, -1]),
+            Dir8::Right => Pt([1, 0]),
+            Dir8::DownRight => Pt([1, 1]),
+            Dir8::Down => Pt([0, 1]),
+            Dir8::DownLeft => Pt([-1, 1]),
+            Dir8::Left => Pt([-1, 0]),
+            Dir8::UpLeft => Pt([-1, -1]),
+        }
+    }
+
+    /// the direction `eighth_turns` eighths of a turn clockwise from this one. Negative turns go anticlockwise
+    pub fn turn(self, eighth_turns: isize) -> Dir8 {
+        Dir8::ALL[(self as isize + eighth_turns).rem_euclid(8) as usize]
+    }
+
+    /// a quarter turn clockwise
+    pub fn turn_right(self) -> Dir8 {
+        self.turn(2)
+    }
+
+    /// a quarter turn anticlockwise
+    pub fn turn_left(self) -> Dir8 {
+        self.turn(-2)
+    }
+
+    pub fn turn_around(self) -> Dir8 {
+        self.turn(4)
+    }
+
+    /// whether this is one of the four cardinal directions
+    pub fn is_cardinal(self) -> bool {
+        Dir4::try_from(self).is_ok()
+    }
+
+    /// the direction a step of `offset` goes in, if it's a single square
+    pub fn from_offset(offset: Pt<2>) -> Option<Dir8> {
+        Dir8::ALL.into_iter().find(|dir| dir.offset() == offset)
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(dir: Dir4) -> Self {
+        Dir8::ALL[dir as usize * 2]
+    }
+}
+
+impl TryFrom<Dir8> for Dir4 {
+    type Error = Error;
+
+    fn try_from(dir: Dir8) -> Result<Self> {
+        match dir as usize {
+            i if i % 2 == 0 => Ok(Dir4::ALL[i / 2]),
+            _ => Err(anyhow!("{:?} is diagonal", dir)),
+        }
+    }
+}
+
+/// `U`/`R`/`D`/`L`, `N`/`E`/`S`/`W`, or an arrow: `^`/`>`/`v`/`<` or `↑`/`→`/`↓`/`←`
+impl TryFrom<char> for Dir4 {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self> {
+        match c {
+            'U' | 'N' | '^' | '↑' => Ok(Dir4::Up),
+            'R' | 'E' | '>' | '→' => Ok(Dir4::Right),
+            'D' | 'S' | 'v' | '↓' => Ok(Dir4::Down),
+            'L' | 'W' | '<' | '←' => Ok(Dir4::Left),
+            _ => Err(anyhow!("Unknown direction '{}'", c)),
+        }
+    }
+}
+
+/// A single character, as for `TryFrom<char>`
+impl FromStr for Dir4 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Dir4::try_from(c),
+            _ => Err(anyhow!("Unknown direction '{}'", s)),
+        }
+    }
+}
+
+/// Anything `Dir4` parses, and the diagonals as a compass point (`NE`), a pair of letters (`UR`),
+/// or an arrow (`↗`)
+impl FromStr for Dir8 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(dir) = s.parse::<Dir4>() {
+            return Ok(dir.into());
+        }
+
+        match s {
+            "NE" | "UR" | "↗" => Ok(Dir8::UpRight),
+            "SE" | "DR" | "↘" => Ok(Dir8::DownRight),
+            "SW" | "DL" | "↙" => Ok(Dir8::DownLeft),
+            "NW" | "UL" | "↖" => Ok(Dir8::UpLeft),
+            _ => Err(anyhow!("Unknown direction '{}'", s)),
+        }
+    }
+}
+
+/// As an arrow, e.g. `^`, for drawing paths on a grid
+impl Display for Dir4 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let arrow = match self {
+            Dir4::Up => '^',
+            Dir4::Right => '>',
+            Dir4::Down => 'v',
+            Dir4::Left => '<',
+        };
+        write!(f, "{}", arrow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dir4, Dir8};
+    use crate::utils::point::Pt;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("U", Dir4::Up)]
+    #[case("E", Dir4::Right)]
+    #[case("v", Dir4::Down)]
+    #[case("←", Dir4::Left)]
+    fn validate_parse(#[case] input: &str, #[case] expected: Dir4) {
+        assert_eq!(expected, input.parse::<Dir4>().unwrap());
+        assert_eq!(Dir8::from(expected), input.parse::<Dir8>().unwrap());
+    }
+
+    #[rstest]
+    #[case("NE", Dir8::UpRight)]
+    #[case("DL", Dir8::DownLeft)]
+    #[case("↖", Dir8::UpLeft)]
+    fn validate_parse_diagonals(#[case] input: &str, #[case] expected: Dir8) {
+        assert_eq!(expected, input.parse::<Dir8>().unwrap());
+        assert!(input.parse::<Dir4>().is_err());
+    }
+
+    #[test]
+    fn test_turns_match_rotating_the_offset() {
+        for dir in Dir4::ALL {
+            assert_eq!(dir.offset().rotate_cw(), dir.turn_right().offset());
+            assert_eq!(dir.offset().rotate_ccw(), dir.turn_left().offset());
+            assert_eq!(-dir.offset(), dir.turn_around().offset());
+            assert_eq!(dir, dir.turn(4));
+            assert_eq!(Some(dir), Dir4::from_offset(dir.offset()));
+        }
+        for dir in Dir8::ALL {
+            assert_eq!(dir.offset().rotate_cw(), dir.turn_right().offset());
+            assert_eq!(-dir.offset(), dir.turn_around().offset());
+            assert_eq!(Some(dir), Dir8::from_offset(dir.offset()));
+        }
+    }
+
+    #[test]
+    fn test_dir8_covers_every_neighbour() {
+        let offsets = Dir8::ALL.map(Dir8::offset).into_iter().collect();
+        assert_eq!(Pt::<2>::neighbour_offsets(), offsets);
+        assert_eq!(
+            4,
+            Dir8::ALL
+                .into_iter()
+                .filter(|dir| dir.is_cardinal())
+                .count()
+        );
+        assert_eq!(Dir8::Left, Dir8::from(Dir4::Left));
+        assert_eq!(Dir4::Down, Dir4::try_from(Dir8::Down).unwrap());
+        assert!(Dir4::try_from(Dir8::DownLeft).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt::Debug;
+
+use anyhow::{anyhow, Result};
+
+use super::solver_types::{SolutionLinear, SolutionSimultaneous};
+
+/// A worked example for a day. Returns an error describing the mismatch if the solution gets it wrong
+pub type Example = fn() -> Result<()>;
+
+/// An expected answer to one part of an example
+pub trait Expect<T> {
+    fn check(&self, actual: &T) -> Result<()>;
+}
+
+/// The example doesn't give an answer for this part
+pub struct Skip;
+
+/// The example's answer for this part
+pub struct Is<E>(pub E);
+
+impl<T> Expect<T> for Skip {
+    fn check(&self, _actual: &T) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: PartialEq<E> + Debug, E: Debug> Expect<T> for Is<E> {
+    fn check(&self, actual: &T) -> Result<()> {
+        if *actual == self.0 {
+            Ok(())
+        } else {
+            Err(anyhow!("expected {:?}, got {:?}", self.0, actual))
+        }
+    }
+}
+
+/// Solve an example with a linear solution and check the answers
+pub fn check_linear<S: SolutionLinear<I, S1, S2>, I, S1: Debug, S2: Debug>(
+    input: &str,
+    expected_1: impl Expect<S1>,
+    expected_2: impl Expect<S2>,
+) -> Result<()> {
+    let mut input = S::load(input)?;
+    let p1 = S::part1(&mut input)?;
+    expected_1
+        .check(&p1)
+        .map_err(|err| anyhow!("part 1: {}", err))?;
+
+    let p2 = S::part2(&mut input, p1)?;
+    expected_2
+        .check(&p2)
+        .map_err(|err| anyhow!("part 2: {}", err))
+}
+
+/// Solve an example with a simultaneous solution and check the answers
+pub fn check_simultaneous<S: SolutionSimultaneous<I, S1, S2>, I, S1: Debug, S2: Debug>(
+    input: &str,
+    expected_1: impl Expect<S1>,
+    expected_2: impl Expect<S2>,
+) -> Result<()> {
+    let input = S::load(input)?;
+    let (p1, p2) = S::solve(input)?;
+    expected_1
+        .check(&p1)
+        .map_err(|err| anyhow!("part 1: {}", err))?;
+    expected_2
+        .check(&p2)
+        .map_err(|err| anyhow!("part 2: {}", err))
+}
+
+/**
+Declare a day's worked examples once, as `(input, expected_1, expected_2)` cases.
+Use `_` for a part the example doesn't give an answer for, or give just the input for a day
+that isn't solved yet, so the example is only loaded.
+
+```ignore
+aoc_examples!(linear Day1Solution;
+    ("1000\n2000", 3000, 3000),
+    ("[1,2,3]", 6, _),
+);
+```
+
+This generates an `EXAMPLES` const, which the runner's example mode uses,
+and a test that checks every example.
+*/
+#[macro_export]
+macro_rules! aoc_examples {
+    (linear $solution:ty; $(($($case:tt)*)),* $(,)?) => {
+        $crate::aoc_examples!(@all check_linear, SolutionLinear, $solution; $(($($case)*)),*);
+    };
+    (simultaneous $solution:ty; $(($($case:tt)*)),* $(,)?) => {
+        $crate::aoc_examples!(@all check_simultaneous, SolutionSimultaneous, $solution; $(($($case)*)),*);
+    };
+    (@all $check:ident, $kind:ident, $solution:ty; $(($($case:tt)*)),*) => {
+        /// The worked examples for this day, shared by the tests and the runner's example mode
+        pub const EXAMPLES: &[$crate::utils::examples::Example] = &[
+            $($crate::aoc_examples!(@case $check, $kind, $solution; $($case)*)),*
+        ];
+
+        /// The inputs of the worked examples, for tests that need realistic input.
+        /// Unused by the templates, which aren't registered as days
+        #[cfg(test)]
+        #[allow(dead_code)]
+        pub const EXAMPLE_INPUTS: &[&str] = &[
+            $($crate::aoc_examples!(@input $($case)*)),*
+        ];
+
+        /// Run just this day's loader, for tests that throw bad input at every day
+        #[cfg(test)]
+        #[allow(dead_code)]
+        pub fn load_only(input: &str) -> anyhow::Result<()> {
+            <$solution as $crate::utils::solver_types::$kind<_, _, _>>::load(input).map(drop)
+        }
+
+        #[cfg(test)]
+        mod examples {
+            #[test]
+            fn validate_examples() {
+                for (i, example) in super::EXAMPLES.iter().enumerate() {
+                    if let Err(err) = example() {
+                        panic!("Example {} failed: {}", i + 1, err);
+                    }
+                }
+            }
+        }
+    };
+    (@input $input:expr $(, $($expected:tt)*)?) => {
+        $input
+    };
+    (@case $check:ident, $kind:ident, $solution:ty; $input:expr) => {
+        || <$solution as $crate::utils::solver_types::$kind<_, _, _>>::load($input).map(drop)
+    };
+    (@case $check:ident, $kind:ident, $solution:ty; $input:expr, _, _) => {
+        $crate::aoc_examples!(@check $check, $solution; $input, $crate::utils::examples::Skip, $crate::utils::examples::Skip)
+    };
+    (@case $check:ident, $kind:ident, $solution:ty; $input:expr, _, $expected_2:expr) => {
+        $crate::aoc_examples!(@check $check, $solution; $input, $crate::utils::examples::Skip, $crate::utils::examples::Is($expected_2))
+    };
+    (@case $check:ident, $kind:ident, $solution:ty; $input:expr, $expected_1:expr, _) => {
+        $crate::aoc_examples!(@check $check, $solution; $input, $crate::utils::examples::Is($expected_1), $crate::utils::examples::Skip)
+    };
+    (@case $check:ident, $kind:ident, $solution:ty; $input:expr, $expected_1:expr, $expected_2:expr) => {
+        $crate::aoc_examples!(@check $check, $solution; $input, $crate::utils::examples::Is($expected_1), $crate::utils::examples::Is($expected_2))
+    };
+    (@check $check:ident, $solution:ty; $input:expr, $expected_1:expr, $expected_2:expr) => {
+        || $crate::utils::examples::$check::<$solution, _, _, _>($input, $expected_1, $expected_2)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_linear, check_simultaneous, Is, Skip};
+    use crate::solutions::templates::{
+        linear_template::ExampleSolutionLinear, simultaneous_template::ExampleSolutionSimultaneous,
+    };
+
+    #[test]
+    fn test_check_linear() {
+        assert!(check_linear::<ExampleSolutionLinear, _, _, _>("[1,2,3]", Is(6), Is(18)).is_ok());
+        assert!(check_linear::<ExampleSolutionLinear, _, _, _>("[1,2,3]", Skip, Is(18)).is_ok());
+
+        let err =
+            check_linear::<ExampleSolutionLinear, _, _, _>("[1,2,3]", Is(6), Is(17)).unwrap_err();
+        assert_eq!("part 2: expected 17, got 18", err.to_string());
+    }
+
+    #[test]
+    fn test_check_simultaneous() {
+        assert!(check_simultaneous::<ExampleSolutionSimultaneous, _, _, _>(
+            "[1,2,6,4,100]",
+            Is(2),
+            Skip
+        )
+        .is_ok());
+
+        let err = check_simultaneous::<ExampleSolutionSimultaneous, _, _, _>(
+            "[1,2,6,4,100]",
+            Is(3),
+            Is(6),
+        )
+        .unwrap_err();
+        assert_eq!("part 1: expected 3, got 2", err.to_string());
+    }
+
+    mod skipping {
+        use crate::solutions::templates::linear_template::ExampleSolutionLinear;
+
+        crate::aoc_examples!(linear ExampleSolutionLinear;
+            ("[1,2,3]", 6, _),
+            ("[1,2,3]", _, 18),
+            ("[1,2,3]", _, _),
+        );
+    }
+
+    #[test]
+    fn test_macro_skips_parts() {
+        assert_eq!(3, skipping::EXAMPLES.len());
+        assert!(skipping::EXAMPLES.iter().all(|example| example().is_ok()));
+    }
+}
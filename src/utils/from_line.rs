@@ -0,0 +1,261 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use regex::{Captures, Match, Regex};
+
+use super::input_error::InputError;
+
+pub use aoc2022_derive::FromLine;
+
+/// Values that are parsed from a line (or block of lines) of input by matching it against a regex.
+/// Usually derived with `#[derive(FromLine)]` and `#[pattern(r"...")]`
+pub trait FromLine: Sized {
+    /// the regex a line must match
+    const PATTERN: &'static str;
+
+    /// build the value from the captures of `PATTERN`
+    fn from_captures(captures: &Captures) -> Result<Self>;
+
+    /// parse a single line. Compiles the pattern every time, so prefer `parse_lines` for many lines
+    fn from_line(line: &str) -> Result<Self> {
+        Ok(from_text(&compile::<Self>(line)?, line)?)
+    }
+}
+
+/// A field that couldn't be parsed, and where its text is in the matched text
+#[derive(Debug)]
+struct FieldError {
+    start: usize,
+    len: usize,
+    message: String,
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for FieldError {}
+
+fn compile<T: FromLine>(input: &str) -> Result<Regex, InputError> {
+    // derived patterns are checked at compile time, so this is only for hand-written impls
+    Regex::new(T::PATTERN)
+        .map_err(|err| InputError::at_line(input, 1, format!("invalid pattern: {}", err)))
+}
+
+/// Match `text` against the pattern. Errors are relative to the start of `text`
+fn from_text<T: FromLine>(regex: &Regex, text: &str) -> Result<T, InputError> {
+    let captures = regex.captures(text).ok_or_else(|| {
+        InputError::at_line(text, 1, format!("expected text matching '{}'", T::PATTERN))
+    })?;
+
+    T::from_captures(&captures).map_err(|err| match err.downcast_ref::<FieldError>() {
+        Some(field) => InputError::at_offset(text, field.start, field.len, &field.message),
+        None => InputError::at_line(text, 1, format!("{:#}", err)),
+    })
+}
+
+/// Parse every non-empty line of the input
+pub fn parse_lines<T: FromLine>(input: &str) -> Result<Vec<T>, InputError> {
+    let regex = compile::<T>(input)?;
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| from_text(&regex, line).map_err(|err| err.shift_lines(i)))
+        .collect()
+}
+
+/// Parse every block of lines in the input, where blocks are separated by blank lines.
+/// The pattern is matched against the whole block, with the lines separated by `\n`
+pub fn parse_blocks<T: FromLine>(input: &str) -> Result<Vec<T>, InputError> {
+    let regex = compile::<T>(input)?;
+    let lines = input.lines().enumerate().collect_vec();
+    lines
+        .split(|(_, line)| line.trim().is_empty())
+        .filter(|block| !block.is_empty())
+        .map(|block| {
+            let (first_line, _) = block[0];
+            let text = block.iter().map(|(_, line)| line).join("\n");
+            from_text(&regex, &text).map_err(|err| err.shift_lines(first_line))
+        })
+        .collect()
+}
+
+/// `text` starts `start` bytes into the matched text
+fn parse_str<T: FromStr>(text: &str, start: usize, field: &str) -> Result<T>
+where
+    T::Err: Display,
+{
+    text.parse::<T>().map_err(|err| {
+        FieldError {
+            start,
+            len: text.chars().count(),
+            message: format!("field '{}' could not parse '{}': {}", field, text, err),
+        }
+        .into()
+    })
+}
+
+/// Parse a field from its capture group. Used by `#[derive(FromLine)]`
+pub fn parse_field<T: FromStr>(capture: Option<Match>, field: &str) -> Result<T>
+where
+    T::Err: Display,
+{
+    let capture = capture.ok_or_else(|| anyhow!("field '{}' wasn't captured", field))?;
+    parse_str(capture.as_str(), capture.start(), field)
+}
+
+/// Parse a comma separated list field from its capture group. Used by `#[derive(FromLine)]`
+pub fn parse_list<T: FromStr>(capture: Option<Match>, field: &str) -> Result<Vec<T>>
+where
+    T::Err: Display,
+{
+    let capture = capture.ok_or_else(|| anyhow!("field '{}' wasn't captured", field))?;
+    if capture.as_str().trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut start = capture.start();
+    let mut items = Vec::new();
+    for item in capture.as_str().split(',') {
+        let leading = item.len() - item.trim_start().len();
+        items.push(parse_str(item.trim(), start + leading, field)?);
+        start += item.len() + 1;
+    }
+    Ok(items)
+}
+
+/// Parse an optional field from its capture group, if it matched. Used by `#[derive(FromLine)]`
+pub fn parse_optional<T: FromStr>(capture: Option<Match>, field: &str) -> Result<Option<T>>
+where
+    T::Err: Display,
+{
+    capture
+        .map(|capture| parse_str(capture.as_str(), capture.start(), field))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_blocks, parse_lines, FromLine};
+    use crate::utils::input_error::Span;
+    use rstest::rstest;
+
+    #[derive(Debug, PartialEq, FromLine)]
+    #[pattern(r"^Valve ([A-Z]+) has flow rate=(\d+); tunnels? leads? to valves? (.*)$")]
+    struct Valve {
+        name: String,
+        rate: u32,
+        tunnels: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, FromLine)]
+    #[pattern(r"^(?P<y>-?\d+),(?P<x>-?\d+)(?: \((?P<label>\w+)\))?$")]
+    struct Labelled {
+        x: i32,
+        y: i32,
+        label: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, FromLine)]
+    #[pattern(r"^(\d+)-(\d+)\n(\d+)-(\d+)$")]
+    struct Pair(u8, u8, u8, u8);
+
+    #[test]
+    fn test_positional_and_lists() {
+        let valves = parse_lines::<Valve>(
+            "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB\n\
+             Valve HH has flow rate=22; tunnel leads to valve GG\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                Valve {
+                    name: "AA".to_string(),
+                    rate: 0,
+                    tunnels: vec!["DD".to_string(), "II".to_string(), "BB".to_string()]
+                },
+                Valve {
+                    name: "HH".to_string(),
+                    rate: 22,
+                    tunnels: vec!["GG".to_string()]
+                }
+            ],
+            valves
+        );
+    }
+
+    #[rstest]
+    #[case("3,-4", Labelled { x: -4, y: 3, label: None })]
+    #[case("0,1 (start)", Labelled { x: 1, y: 0, label: Some("start".to_string()) })]
+    fn validate_named_and_optional(#[case] line: &str, #[case] expected: Labelled) {
+        assert_eq!(expected, Labelled::from_line(line).unwrap());
+    }
+
+    #[test]
+    fn test_blocks() {
+        let pairs = parse_blocks::<Pair>("1-2\n3-4\n\n5-6\n7-8\n").unwrap();
+        assert_eq!(vec![Pair(1, 2, 3, 4), Pair(5, 6, 7, 8)], pairs);
+    }
+
+    #[rstest]
+    #[case(
+        "Valve AA has flow rate=0; tunnels lead to valves DD\nValve BB has flow rate=x; tunnel leads to valve AA",
+        Span { line: 2, column: 1, len: 50 },
+        "expected text matching"
+    )]
+    #[case(
+        "Valve AA has flow rate=99999999999; tunnels lead to valves DD",
+        Span { line: 1, column: 24, len: 11 },
+        "field 'rate' could not parse '99999999999': number too large to fit in target type"
+    )]
+    fn validate_errors(#[case] input: &str, #[case] span: Span, #[case] expected: &str) {
+        let err = parse_lines::<Valve>(input).unwrap_err();
+        assert_eq!(span, err.span);
+        assert!(
+            err.message.starts_with(expected),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[derive(Debug, FromLine)]
+    #[pattern(r"^Starting items: (.*)$")]
+    struct Items {
+        _items: Vec<u32>,
+    }
+
+    #[test]
+    fn test_list_errors_point_at_the_item() {
+        let err = parse_lines::<Items>("\nStarting items: 79,  x8, 3").unwrap_err();
+        assert_eq!(
+            Span {
+                line: 2,
+                column: 22,
+                len: 2
+            },
+            err.span
+        );
+        assert_eq!(
+            "field '_items' could not parse 'x8': invalid digit found in string",
+            err.message
+        );
+    }
+
+    #[test]
+    fn test_block_errors_point_into_the_block() {
+        let err = parse_blocks::<Pair>("1-2\n3-4\n\n5-6\n7-800\n").unwrap_err();
+        assert_eq!(
+            Span {
+                line: 5,
+                column: 3,
+                len: 3
+            },
+            err.span
+        );
+    }
+}
@@ -1,69 +1,159 @@
 use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
 use std::fmt::Debug;
+use std::iter;
 
-use super::point::Pt;
+use anyhow::Result;
+use itertools::{Either, Itertools};
+use num::iter::range_inclusive;
 
-#[allow(dead_code)]
+use super::{
+    orientation::Orientation,
+    point::{Pt, Scalar, AXES},
+};
+
+/// Which points count as next to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjacency {
+    /// only along an axis, e.g. the 4 neighbours of a point in 2D (von Neumann)
+    Cardinal,
+    /// including diagonals, e.g. all 8 neighbours of a point in 2D (Moore)
+    Full,
+}
+
+impl Adjacency {
+    /// the offsets from a point to each of its neighbours
+    pub fn offsets<const DIMS: usize, S: Scalar>(self) -> HashSet<Pt<DIMS, S>> {
+        match self {
+            Adjacency::Cardinal => Pt::card_offsets(),
+            Adjacency::Full => Pt::neighbour_offsets(),
+        }
+    }
+}
+
+/// How `Grid::print_with` draws a grid
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Grid<T: Copy, const DIMS: usize> {
+pub struct PrintOptions<const DIMS: usize, S: Scalar = isize> {
+    /// the points to print, or `None` for every stored and highlighted point
+    pub window: Option<([S; DIMS], [S; DIMS])>,
+    /// print x decreasing from left to right
+    pub flip_x: bool,
+    /// print y increasing upwards, e.g. for puzzles where up is positive
+    pub flip_y: bool,
+    /// number the rows and columns with their coordinates
+    pub labels: bool,
+    /// points to draw as the given character instead of their value
+    pub highlights: HashMap<Pt<DIMS, S>, char>,
+}
+
+impl<const DIMS: usize, S: Scalar> Default for PrintOptions<DIMS, S> {
+    fn default() -> Self {
+        Self {
+            window: None,
+            flip_x: false,
+            flip_y: false,
+            labels: false,
+            highlights: HashMap::new(),
+        }
+    }
+}
+
+/// Values of type `T` at points in `DIMS` dimensions, with coordinates of type `S`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T: Copy, const DIMS: usize, S: Scalar = isize> {
     /// neighbour offsets for points in this N dimensions
-    offsets: HashSet<Pt<DIMS>>,
+    offsets: HashSet<Pt<DIMS, S>>,
     /// cardinal offsets for points in this N dimensions
-    card_offsets: HashSet<Pt<DIMS>>,
+    card_offsets: HashSet<Pt<DIMS, S>>,
     default_val: T,
-    pub grid: HashMap<Pt<DIMS>, T>,
+    pub grid: HashMap<Pt<DIMS, S>, T>,
 }
 
-impl<T: Default + Copy, const DIMS: usize> Default for Grid<T, DIMS> {
+impl<T: Default + Copy, const DIMS: usize, S: Scalar> Default for Grid<T, DIMS, S> {
     fn default() -> Self {
         Self {
-            offsets: Pt::<DIMS>::neighbour_offsets(),
-            card_offsets: Pt::<DIMS>::card_offsets(),
+            offsets: Pt::<DIMS, S>::neighbour_offsets(),
+            card_offsets: Pt::<DIMS, S>::card_offsets(),
             default_val: T::default(),
             grid: Default::default(),
         }
     }
 }
 
-impl<T: Default + Copy, const DIMS: usize> From<Vec<(Pt<DIMS>, T)>> for Grid<T, DIMS> {
-    fn from(v: Vec<(Pt<DIMS>, T)>) -> Self {
+impl<T: Default + Copy, const DIMS: usize, S: Scalar> From<Vec<(Pt<DIMS, S>, T)>>
+    for Grid<T, DIMS, S>
+{
+    fn from(v: Vec<(Pt<DIMS, S>, T)>) -> Self {
         Self {
-            offsets: Pt::<DIMS>::neighbour_offsets(),
-            card_offsets: Pt::<DIMS>::card_offsets(),
+            offsets: Pt::<DIMS, S>::neighbour_offsets(),
+            card_offsets: Pt::<DIMS, S>::card_offsets(),
             default_val: T::default(),
             grid: v.into_iter().collect(),
         }
     }
 }
 
-impl<T: Default + Copy, const DIMS: usize> From<Vec<(Vec<isize>, T)>> for Grid<T, DIMS> {
-    fn from(v: Vec<(Vec<isize>, T)>) -> Self {
+impl<T: Default + Copy, const DIMS: usize, S: Scalar> From<Vec<(Vec<S>, T)>> for Grid<T, DIMS, S> {
+    fn from(v: Vec<(Vec<S>, T)>) -> Self {
         Self {
-            offsets: Pt::<DIMS>::neighbour_offsets(),
-            card_offsets: Pt::<DIMS>::card_offsets(),
+            offsets: Pt::<DIMS, S>::neighbour_offsets(),
+            card_offsets: Pt::<DIMS, S>::card_offsets(),
             default_val: T::default(),
             grid: v
                 .into_iter()
-                .map(|(k, v)| (Pt(k.try_into().unwrap()), v))
+                .map(|(k, v)| (Pt::try_from(k).unwrap(), v))
                 .collect(),
         }
     }
 }
 
 #[allow(dead_code)]
-impl<T: Copy, const DIMS: usize> Grid<T, DIMS> {
+impl<T: Default + Copy> Grid<T, 2> {
+    /// a grid from lines of text, with the first line at `y = 0` and each line going left to right
+    /// from `x = 0`, where `parse` gives the value of each character
+    pub fn from_text(input: &str, parse: impl Fn(char) -> T) -> Self {
+        match Self::try_from_text(input, |_, c| Ok::<_, Infallible>(parse(c))) {
+            Ok(grid) => grid,
+            Err(never) => match never {},
+        }
+    }
+
+    /// like `from_text`, but `parse` is also given the point and can fail, e.g. with an
+    /// `InputError` pointing at the character. Stops at the first error
+    pub fn try_from_text<E>(
+        input: &str,
+        mut parse: impl FnMut(Pt<2>, char) -> Result<T, E>,
+    ) -> Result<Self, E> {
+        let mut grid = Self::default();
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let pt = Pt([x as isize, y as isize]);
+                grid.grid.insert(pt, parse(pt, c)?);
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Copy, const DIMS: usize, S: Scalar> Grid<T, DIMS, S> {
     /// get a value at the specified coordinates or the default
-    pub fn get_def(&self, pt: &Pt<DIMS>) -> T {
+    pub fn get_def(&self, pt: &Pt<DIMS, S>) -> T {
         *self.grid.get(pt).unwrap_or(&self.default_val)
     }
 
+    /// the value of every point that isn't stored
+    pub fn default_value(&self) -> T {
+        self.default_val
+    }
+
     /// Change the default value
     pub fn set_default(&mut self, new_default: T) {
         self.default_val = new_default;
     }
 
     /// merge one grid into this one, using the specified merge_function
-    pub fn merge(&mut self, other: Grid<T, DIMS>, merge_function: fn(&T, &T) -> T) {
+    pub fn merge(&mut self, other: Grid<T, DIMS, S>, merge_function: fn(&T, &T) -> T) {
         other.grid.into_iter().for_each(|(k, v)| {
             let new_val = merge_function(self.grid.get(&k).unwrap_or(&self.default_val), &v);
             self.grid.insert(k, new_val);
@@ -71,7 +161,7 @@ impl<T: Copy, const DIMS: usize> Grid<T, DIMS> {
     }
 
     /// apply a transformation to every point in a grid
-    pub fn transform(mut self, transformation: fn(Pt<DIMS>) -> Pt<DIMS>) -> Self {
+    pub fn transform(mut self, transformation: impl Fn(Pt<DIMS, S>) -> Pt<DIMS, S>) -> Self {
         let mut new_grid = HashMap::default();
         self.grid.into_iter().for_each(|(k, v)| {
             new_grid.insert(transformation(k), v);
@@ -80,30 +170,166 @@ impl<T: Copy, const DIMS: usize> Grid<T, DIMS> {
         self
     }
 
+    /// rotate and/or reflect the grid about the origin
+    pub fn orient(self, orientation: Orientation<DIMS>) -> Self {
+        self.transform(|pt| orientation.apply(pt))
+    }
+
     /// get the min and max values of each dimension
-    pub fn bounds(&self) -> ([isize; DIMS], [isize; DIMS]) {
-        let mut mins = [0; DIMS];
-        let mut maxs = [0; DIMS];
+    pub fn bounds(&self) -> ([S; DIMS], [S; DIMS]) {
+        let mut mins = [S::zero(); DIMS];
+        let mut maxs = [S::zero(); DIMS];
 
         for k in self.grid.keys() {
             for i in 0..DIMS {
-                mins[i] = isize::min(mins[i], k.0[i]);
-                maxs[i] = isize::max(maxs[i], k.0[i]);
+                mins[i] = S::min(mins[i], k.0[i]);
+                maxs[i] = S::max(maxs[i], k.0[i]);
             }
         }
         (mins, maxs)
     }
+
+    /// the min and max values of each dimension for only the points stored, or `None` if there
+    /// aren't any. `bounds` always includes the origin
+    pub fn stored_bounds(&self) -> Option<([S; DIMS], [S; DIMS])> {
+        bounds_of(self.grid.keys())
+    }
+
+    /// every neighbour of `pt`, including diagonals
+    pub fn neighbours<'a>(&'a self, pt: &'a Pt<DIMS, S>) -> impl Iterator<Item = Pt<DIMS, S>> + 'a {
+        self.offsets.iter().map(move |offset| *pt + *offset)
+    }
+
+    /// the cardinal (non-diagonal) neighbours of `pt`
+    pub fn card_neighbours<'a>(
+        &'a self,
+        pt: &'a Pt<DIMS, S>,
+    ) -> impl Iterator<Item = Pt<DIMS, S>> + 'a {
+        self.card_offsets.iter().map(move |offset| *pt + *offset)
+    }
+
+    /// the neighbours of `pt`, including diagonals, that are inside `bounds` (e.g. from `bounds()`)
+    pub fn neighbours_in_bounds<'a>(
+        &'a self,
+        pt: &'a Pt<DIMS, S>,
+        (mins, maxs): ([S; DIMS], [S; DIMS]),
+    ) -> impl Iterator<Item = Pt<DIMS, S>> + 'a {
+        self.neighbours(pt)
+            .filter(move |n| (0..DIMS).all(|i| mins[i] <= n.0[i] && n.0[i] <= maxs[i]))
+    }
+
+    /// every neighbour of `pt`, including diagonals, with its value or the default
+    pub fn neighbour_values<'a>(
+        &'a self,
+        pt: &'a Pt<DIMS, S>,
+    ) -> impl Iterator<Item = (Pt<DIMS, S>, T)> + 'a {
+        self.neighbours(pt).map(|n| (n, self.get_def(&n)))
+    }
+
+    /// the cardinal neighbours of `pt`, with their values or the default
+    pub fn card_neighbour_values<'a>(
+        &'a self,
+        pt: &'a Pt<DIMS, S>,
+    ) -> impl Iterator<Item = (Pt<DIMS, S>, T)> + 'a {
+        self.card_neighbours(pt).map(|n| (n, self.get_def(&n)))
+    }
+
+    /// the neighbours of `pt` with the given `adjacency`, with their values or the default
+    pub fn adjacent_values<'a>(
+        &'a self,
+        pt: &'a Pt<DIMS, S>,
+        adjacency: Adjacency,
+    ) -> impl Iterator<Item = (Pt<DIMS, S>, T)> + 'a {
+        match adjacency {
+            Adjacency::Cardinal => Either::Left(self.card_neighbour_values(pt)),
+            Adjacency::Full => Either::Right(self.neighbour_values(pt)),
+        }
+    }
+
+    /// the neighbours of `pt`, including diagonals, that are stored in the grid, with their values
+    pub fn existing_neighbours<'a>(
+        &'a self,
+        pt: &'a Pt<DIMS, S>,
+    ) -> impl Iterator<Item = (Pt<DIMS, S>, T)> + 'a {
+        self.neighbours(pt)
+            .filter_map(|n| self.grid.get(&n).map(|value| (n, *value)))
+    }
+
+    /// the cardinal neighbours of `pt` that are stored in the grid, with their values
+    pub fn existing_card_neighbours<'a>(
+        &'a self,
+        pt: &'a Pt<DIMS, S>,
+    ) -> impl Iterator<Item = (Pt<DIMS, S>, T)> + 'a {
+        self.card_neighbours(pt)
+            .filter_map(|n| self.grid.get(&n).map(|value| (n, *value)))
+    }
+}
+
+/// Looking along a line through a grid. Rays only cross points stored in the grid, so they stop at
+/// its edge even if it has a default value. `step` can be any vector except zero, which would
+/// never leave the grid and so panics, e.g. `Dir8::offset` in 2D or a knight's move
+#[allow(dead_code)]
+impl<T: Copy, const DIMS: usize, S: Scalar> Grid<T, DIMS, S> {
+    /// the points from `from` (not including it) in steps of `step`, up to the edge of the grid
+    pub fn ray(
+        &self,
+        from: &Pt<DIMS, S>,
+        step: Pt<DIMS, S>,
+    ) -> impl Iterator<Item = (Pt<DIMS, S>, T)> + '_ {
+        assert!(step != Pt::default(), "a ray needs a non-zero step");
+        iter::successors(Some(*from + step), move |pt| Some(*pt + step))
+            .map_while(|pt| self.grid.get(&pt).map(|value| (pt, *value)))
+    }
+
+    /// the points that can be seen from `from` looking along `step`, up to and including the first
+    /// one that `blocks` the view
+    pub fn line_of_sight<'a>(
+        &'a self,
+        from: &Pt<DIMS, S>,
+        step: Pt<DIMS, S>,
+        blocks: impl Fn(T) -> bool + 'a,
+    ) -> impl Iterator<Item = (Pt<DIMS, S>, T)> + 'a {
+        let mut blocked = false;
+        self.ray(from, step).take_while(move |(_, value)| {
+            let seen = !blocked;
+            blocked = blocked || blocks(*value);
+            seen
+        })
+    }
+
+    /// the first point looking from `from` along `step` that `blocks` the view, if there is one
+    pub fn first_blocker(
+        &self,
+        from: &Pt<DIMS, S>,
+        step: Pt<DIMS, S>,
+        blocks: impl Fn(T) -> bool,
+    ) -> Option<(Pt<DIMS, S>, T)> {
+        self.ray(from, step).find(|(_, value)| blocks(*value))
+    }
+
+    /// whether nothing `blocks` the view from `pt` to the edge of the grid along any of `steps`,
+    /// e.g. `Dir4::ALL.map(Dir4::offset)`
+    pub fn visible_from_outside(
+        &self,
+        pt: &Pt<DIMS, S>,
+        steps: impl IntoIterator<Item = Pt<DIMS, S>>,
+        blocks: impl Fn(T) -> bool,
+    ) -> bool {
+        steps
+            .into_iter()
+            .any(|step| self.first_blocker(pt, step, &blocks).is_none())
+    }
 }
 
-impl<T: Copy> Grid<T, 2> {
+impl<T: Copy, S: Scalar> Grid<T, 2, S> {
     /// print a 2d grid using a given function for representing points
     pub fn print(&self, to_printable: fn(T) -> char) -> String {
         let mut res = String::from("\n");
 
         let ([min_x, min_y], [max_x, max_y]) = self.bounds();
 
-        for y in min_y..max_y + 1 {
-            for x in min_x..max_x + 1 {
+        for y in range_inclusive(min_y, max_y) {
+            for x in range_inclusive(min_x, max_x) {
                 res.push(to_printable(self.get_def(&Pt([x, y]))));
             }
             res.push('\n');
@@ -113,10 +339,214 @@ impl<T: Copy> Grid<T, 2> {
     }
 }
 
+#[allow(dead_code)]
+impl<T: Copy, const DIMS: usize, S: Scalar> Grid<T, DIMS, S> {
+    /// print a grid with the given `options`, using a given function for representing points.
+    /// Grids with more than 2 dimensions are printed as a 2D slice for each layer, e.g. each `z`
+    pub fn print_with(
+        &self,
+        to_printable: fn(T) -> char,
+        options: &PrintOptions<DIMS, S>,
+    ) -> String {
+        assert!(
+            DIMS >= 2,
+            "Only grids with at least 2 dimensions can be printed"
+        );
+
+        let window = options
+            .window
+            .or_else(|| bounds_of(self.grid.keys().chain(options.highlights.keys())));
+        let Some((mins, maxs)) = window else {
+            return String::new();
+        };
+
+        let axis = |i: usize| {
+            let values = range_inclusive(mins[i], maxs[i]).collect_vec();
+            let flipped = (i == 0 && options.flip_x) || (i == 1 && options.flip_y);
+            if flipped {
+                values.into_iter().rev().collect_vec()
+            } else {
+                values
+            }
+        };
+        let (xs, ys) = (axis(0), axis(1));
+
+        let layers = if DIMS == 2 {
+            vec![vec![]]
+        } else {
+            (2..DIMS).map(axis).multi_cartesian_product().collect_vec()
+        };
+
+        layers
+            .into_iter()
+            .map(|layer| {
+                let mut pt = Pt([S::zero(); DIMS]);
+                pt.0[2..].copy_from_slice(&layer);
+
+                let slice = print_slice(&xs, &ys, options.labels, |x, y| {
+                    let mut pt = pt;
+                    pt.0[0] = x;
+                    pt.0[1] = y;
+                    options
+                        .highlights
+                        .get(&pt)
+                        .copied()
+                        .unwrap_or_else(|| to_printable(self.get_def(&pt)))
+                });
+
+                if DIMS == 2 {
+                    slice
+                } else {
+                    let name = layer
+                        .iter()
+                        .enumerate()
+                        .map(|(i, value)| match AXES.get(i + 2) {
+                            Some(axis) => format!("{}={}", axis, value),
+                            None => format!("axis {}={}", i + 2, value),
+                        })
+                        .join(", ");
+                    format!("{}\n{}", name, slice)
+                }
+            })
+            .join("\n\n")
+    }
+}
+
+/// the min and max values of each dimension of `pts`, or `None` if there aren't any
+fn bounds_of<'a, const DIMS: usize, S: Scalar + 'a>(
+    mut pts: impl Iterator<Item = &'a Pt<DIMS, S>>,
+) -> Option<([S; DIMS], [S; DIMS])> {
+    let first = pts.next()?;
+    Some(pts.fold((first.0, first.0), |(mut mins, mut maxs), pt| {
+        for i in 0..DIMS {
+            mins[i] = mins[i].min(pt.0[i]);
+            maxs[i] = maxs[i].max(pt.0[i]);
+        }
+        (mins, maxs)
+    }))
+}
+
+/// the rows of a 2D slice, from the first of `ys` to the last. With `labels`, each row starts
+/// with its y coordinate, and above them each column's x coordinate is written downwards
+fn print_slice<S: Scalar>(xs: &[S], ys: &[S], labels: bool, cell: impl Fn(S, S) -> char) -> String {
+    let mut rows = ys
+        .iter()
+        .map(|y| xs.iter().map(|x| cell(*x, *y)).collect::<String>());
+
+    if !labels {
+        return rows.join("\n");
+    }
+
+    let width = |values: &[S]| {
+        values
+            .iter()
+            .map(|v| v.to_string().len())
+            .max()
+            .unwrap_or(0)
+    };
+    let (x_width, y_width) = (width(xs), width(ys));
+
+    let x_labels = xs
+        .iter()
+        .map(|x| format!("{:>1$}", x, x_width).chars().collect_vec())
+        .collect_vec();
+    let header = (0..x_width).map(|i| {
+        let digits = x_labels.iter().map(|label| label[i]).collect::<String>();
+        format!("{:1$} {2}", "", y_width, digits)
+    });
+
+    let labelled_rows = ys
+        .iter()
+        .zip(rows)
+        .map(|(y, row)| format!("{:>1$} {2}", y, y_width, row));
+
+    header.chain(labelled_rows).join("\n")
+}
+
+/// What `Grid` and `DenseGrid` have in common, so an algorithm can be written once for both.
+/// A `Grid` is sparse and extends forever, and a `DenseGrid` stores every cell inside fixed bounds
+pub trait GridLike<T: Copy, const DIMS: usize, S: Scalar = isize> {
+    /// the value at `pt`, or `None` if it's outside the grid.
+    /// A `Grid` has no outside, so it gives its default value for points it doesn't store
+    fn get(&self, pt: &Pt<DIMS, S>) -> Option<T>;
+
+    /// set the value at `pt`, or fail if it's outside the grid
+    fn set(&mut self, pt: Pt<DIMS, S>, value: T) -> Result<()>;
+
+    /// the min and max values of each dimension
+    fn bounds(&self) -> ([S; DIMS], [S; DIMS]);
+
+    /// every point the grid stores, with its value
+    fn cells(&self) -> Box<dyn Iterator<Item = (Pt<DIMS, S>, T)> + '_>;
+
+    fn contains(&self, pt: &Pt<DIMS, S>) -> bool {
+        self.get(pt).is_some()
+    }
+
+    /// the neighbours of `pt` that are in the grid, including diagonals, with their values.
+    /// Like `Grid::neighbour_values`, which this is for a `Grid`
+    fn neighbour_values(&self, pt: &Pt<DIMS, S>) -> Vec<(Pt<DIMS, S>, T)> {
+        values_at(self, *pt, Pt::neighbour_offsets())
+    }
+
+    /// the cardinal (non-diagonal) neighbours of `pt` that are in the grid, with their values
+    fn card_neighbour_values(&self, pt: &Pt<DIMS, S>) -> Vec<(Pt<DIMS, S>, T)> {
+        values_at(self, *pt, Pt::card_offsets())
+    }
+}
+
+/// the points at each of `offsets` from `pt` that are in the grid, with their values, in a fixed
+/// order. Backends that are meant to be fast override the methods using this with stored offsets
+fn values_at<T: Copy, const DIMS: usize, S: Scalar, G: GridLike<T, DIMS, S> + ?Sized>(
+    grid: &G,
+    pt: Pt<DIMS, S>,
+    offsets: HashSet<Pt<DIMS, S>>,
+) -> Vec<(Pt<DIMS, S>, T)> {
+    offsets
+        .into_iter()
+        .sorted()
+        .filter_map(|offset| {
+            let neighbour = pt + offset;
+            grid.get(&neighbour).map(|value| (neighbour, value))
+        })
+        .collect()
+}
+
+impl<T: Copy, const DIMS: usize, S: Scalar> GridLike<T, DIMS, S> for Grid<T, DIMS, S> {
+    fn get(&self, pt: &Pt<DIMS, S>) -> Option<T> {
+        Some(self.get_def(pt))
+    }
+
+    fn set(&mut self, pt: Pt<DIMS, S>, value: T) -> Result<()> {
+        self.grid.insert(pt, value);
+        Ok(())
+    }
+
+    fn bounds(&self) -> ([S; DIMS], [S; DIMS]) {
+        Grid::bounds(self)
+    }
+
+    fn cells(&self) -> Box<dyn Iterator<Item = (Pt<DIMS, S>, T)> + '_> {
+        Box::new(self.grid.iter().map(|(pt, value)| (*pt, *value)))
+    }
+
+    fn neighbour_values(&self, pt: &Pt<DIMS, S>) -> Vec<(Pt<DIMS, S>, T)> {
+        Grid::neighbour_values(self, pt).collect()
+    }
+
+    fn card_neighbour_values(&self, pt: &Pt<DIMS, S>) -> Vec<(Pt<DIMS, S>, T)> {
+        Grid::card_neighbour_values(self, pt).collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Grid;
-    use crate::utils::point::Pt;
+    use std::collections::HashMap;
+
+    use itertools::Itertools;
+
+    use super::{Grid, PrintOptions};
+    use crate::utils::{orientation::Orientation, point::Pt};
 
     #[test]
     fn test_transform() {
@@ -137,6 +567,49 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn test_from_text() {
+        let grid = Grid::from_text("#.\n.##", |c| c == '#');
+        assert_eq!(5, grid.grid.len());
+        assert!(grid.get_def(&Pt([2, 1])));
+        assert!(!grid.get_def(&Pt([0, 1])));
+        // rows can be different lengths, leaving the rest of a short row as the default
+        assert_eq!("\n#..\n.##\n", grid.print(|x| if x { '#' } else { '.' }));
+
+        let digits = Grid::<u32, 2>::try_from_text("12\n3x", |pt, c| c.to_digit(10).ok_or(pt));
+        assert_eq!(Err(Pt([1, 1])), digits);
+    }
+
+    #[test]
+    fn test_orient() {
+        let input = Grid::<u32, 2>::from(vec![(Pt([0, 0]), 1), (Pt([1, 0]), 2), (Pt([1, 1]), 3)]);
+
+        let rotated = input.clone().orient(Orientation::quarter_turn(0, 1));
+        assert_eq!(
+            "\n01\n32\n",
+            rotated.print(|x| char::from_digit(x, 10).unwrap())
+        );
+
+        let all_the_way_round = (0..4).fold(input.clone(), |grid, _| {
+            grid.orient(Orientation::quarter_turn(0, 1))
+        });
+        assert_eq!(input, all_the_way_round);
+    }
+
+    #[test]
+    fn test_small_coordinates() {
+        let grid = Grid::<u32, 2, i16>::from(vec![(Pt([-1, 0]), 1), (Pt([1, 1]), 2)]);
+
+        assert_eq!(([-1, 0], [1, 1]), grid.bounds());
+        assert_eq!(
+            "\n100\n002\n",
+            grid.print(|x| char::from_digit(x, 10).unwrap())
+        );
+
+        let rotated = grid.orient(Orientation::quarter_turn(0, 1));
+        assert_eq!(2, rotated.get_def(&Pt([-1, 1])));
+    }
+
     #[test]
     fn test_merge() {
         let mut target = Grid::<i32, 2>::from(vec![(Pt([50, 50]), 10), (Pt([25, 50]), 204)]);
@@ -153,6 +626,167 @@ mod tests {
         assert_eq!(expected, target);
     }
 
+    #[test]
+    fn test_neighbours() {
+        let mut grid =
+            Grid::<u32, 2>::from(vec![(Pt([0, 0]), 1), (Pt([1, 0]), 2), (Pt([1, 1]), 3)]);
+        grid.set_default(9);
+        let origin = Pt([0, 0]);
+
+        assert_eq!(8, grid.neighbours(&origin).count());
+        assert_eq!(
+            vec![Pt([-1, 0]), Pt([0, -1]), Pt([0, 1]), Pt([1, 0])],
+            grid.card_neighbours(&origin).sorted().collect_vec()
+        );
+        assert_eq!(
+            vec![Pt([0, 1]), Pt([1, 0]), Pt([1, 1])],
+            grid.neighbours_in_bounds(&origin, grid.bounds())
+                .sorted()
+                .collect_vec()
+        );
+
+        assert_eq!(
+            vec![(Pt([0, 1]), 9), (Pt([1, 0]), 2)],
+            grid.card_neighbour_values(&origin)
+                .filter(|(pt, _)| pt.0 >= [0, 0])
+                .sorted()
+                .collect_vec()
+        );
+        // two stored neighbours, and the default for the other six
+        assert_eq!(
+            2 + 3 + 9 * 6,
+            grid.neighbour_values(&origin).map(|(_, v)| v).sum::<u32>()
+        );
+        assert_eq!(
+            vec![(Pt([1, 0]), 2), (Pt([1, 1]), 3)],
+            grid.existing_neighbours(&origin).sorted().collect_vec()
+        );
+        assert_eq!(
+            vec![(Pt([1, 0]), 2)],
+            grid.existing_card_neighbours(&origin).collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_rays() {
+        let grid = Grid::<u32, 2>::from(
+            (0..5)
+                .cartesian_product(0..5)
+                .map(|(x, y)| (Pt([x, y]), (x * y) as u32))
+                .collect_vec(),
+        );
+        let from = Pt([1, 1]);
+
+        assert_eq!(
+            vec![(Pt([2, 2]), 4), (Pt([3, 3]), 9), (Pt([4, 4]), 16)],
+            grid.ray(&from, Pt([1, 1])).collect_vec()
+        );
+        // a knight's move leaves the grid after one step
+        assert_eq!(
+            vec![(Pt([3, 2]), 6)],
+            grid.ray(&from, Pt([2, 1])).collect_vec()
+        );
+        assert_eq!(
+            vec![(Pt([0, 0]), 0)],
+            grid.ray(&from, Pt([-1, -1])).collect_vec()
+        );
+
+        let blocks = |value| value >= 3;
+        assert_eq!(
+            vec![Pt([2, 1]), Pt([3, 1])],
+            grid.line_of_sight(&from, Pt([1, 0]), blocks)
+                .map(|(pt, _)| pt)
+                .collect_vec()
+        );
+        assert_eq!(
+            Some((Pt([3, 1]), 3)),
+            grid.first_blocker(&from, Pt([1, 0]), blocks)
+        );
+        assert_eq!(None, grid.first_blocker(&from, Pt([0, -1]), blocks));
+
+        let centre = Pt([2, 2]);
+        assert!(!grid.visible_from_outside(&centre, [Pt([1, 0]), Pt([0, 1])], blocks));
+        assert!(grid.visible_from_outside(&centre, [Pt([1, 0]), Pt([0, -1])], blocks));
+    }
+
+    #[test]
+    #[should_panic(expected = "a ray needs a non-zero step")]
+    fn test_ray_with_no_step() {
+        let grid = Grid::<u32, 2>::from(vec![(Pt([0, 0]), 1), (Pt([1, 0]), 2)]);
+        grid.ray(&Pt([0, 0]), Pt([0, 0])).count();
+    }
+
+    #[test]
+    fn test_print_with() {
+        let mut grid = Grid::<char, 2>::from(vec![
+            (Pt([-1, 8]), '1'),
+            (Pt([1, 10]), '2'),
+            (Pt([0, 9]), '3'),
+        ]);
+        grid.set_default('.');
+        let same = |c| c;
+
+        // x labels are written downwards, so -1 takes two rows
+        assert_eq!(
+            "   -  \n   101\n 8 1..\n 9 .3.\n10 ..2",
+            grid.print_with(
+                same,
+                &PrintOptions {
+                    labels: true,
+                    ..Default::default()
+                }
+            )
+        );
+
+        assert_eq!(
+            "..2\n#3.\n1..",
+            grid.print_with(
+                same,
+                &PrintOptions {
+                    flip_y: true,
+                    highlights: HashMap::from([(Pt([-1, 9]), '#')]),
+                    ..Default::default()
+                }
+            )
+        );
+
+        assert_eq!(
+            "   -\n  01\n8 .1\n9 3.",
+            grid.print_with(
+                same,
+                &PrintOptions {
+                    window: Some(([-1, 8], [0, 9])),
+                    flip_x: true,
+                    labels: true,
+                    ..Default::default()
+                }
+            )
+        );
+
+        assert_eq!(
+            "",
+            Grid::<char, 2>::default().print_with(same, &PrintOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_print_layers() {
+        let mut cube = Grid::<char, 3>::from(vec![(Pt([0, 0, 0]), '#'), (Pt([1, 1, 1]), '#')]);
+        cube.set_default('.');
+        assert_eq!(
+            "z=0\n#.\n..\n\nz=1\n..\n.#",
+            cube.print_with(|c| c, &PrintOptions::default())
+        );
+
+        let mut tesseract =
+            Grid::<char, 4>::from(vec![(Pt([0, 0, 0, 0]), '#'), (Pt([0, 0, 1, -1]), '#')]);
+        tesseract.set_default('.');
+        assert_eq!(
+            "z=0, w=-1\n.\n\nz=0, w=0\n#\n\nz=1, w=-1\n#\n\nz=1, w=0\n.",
+            tesseract.print_with(|c| c, &PrintOptions::default())
+        );
+    }
+
     #[test]
     fn test_print() {
         let expected = r#"
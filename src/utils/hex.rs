@@ -0,0 +1,349 @@
+//! Hexagonal grids, in axial coordinates.
+//!
+//! A `Hex` is a column `q` and a row `r`, where rows run left to right across the screen like a
+//! pointy-topped hex grid, and each row is shifted half a hex right of the one above it.
+//! The third cube coordinate `s` is implied by `q + r + s = 0`.
+//! Puzzles often give hex positions in offset coordinates instead, see `OffsetLayout`.
+//! See https://www.redblobgames.com/grids/hexagons/ for the details.
+
+use std::{
+    collections::HashMap,
+    ops::{Add, AddAssign, Mul, Neg, Sub},
+};
+
+use super::point::Pt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Hex {
+    pub q: isize,
+    pub r: isize,
+}
+
+/// How a puzzle numbers the hexes of a grid as columns and rows.
+/// `OddR`/`EvenR` are pointy-topped with the odd/even rows pushed right,
+/// and `OddQ`/`EvenQ` are flat-topped with the odd/even columns pushed down
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetLayout {
+    OddR,
+    EvenR,
+    OddQ,
+    EvenQ,
+}
+
+#[allow(dead_code)]
+impl Hex {
+    /// the offsets to the six neighbours, clockwise from the right
+    pub const NEIGHBOUR_OFFSETS: [Hex; 6] = [
+        Hex { q: 1, r: 0 },
+        Hex { q: 0, r: 1 },
+        Hex { q: -1, r: 1 },
+        Hex { q: -1, r: 0 },
+        Hex { q: 0, r: -1 },
+        Hex { q: 1, r: -1 },
+    ];
+
+    pub fn new(q: isize, r: isize) -> Self {
+        Self { q, r }
+    }
+
+    /// the third cube coordinate
+    pub fn s(&self) -> isize {
+        -self.q - self.r
+    }
+
+    pub fn neighbours(self) -> [Hex; 6] {
+        Self::NEIGHBOUR_OFFSETS.map(|offset| self + offset)
+    }
+
+    /// the number of steps from the origin
+    pub fn dist_from_origin(&self) -> isize {
+        (self.q.abs() + self.r.abs() + self.s().abs()) / 2
+    }
+
+    /// the number of steps to `other`
+    pub fn dist(&self, other: Hex) -> isize {
+        (other - *self).dist_from_origin()
+    }
+
+    /// a sixth of a turn clockwise about the origin
+    pub fn rotate_cw(self) -> Self {
+        Self {
+            q: -self.r,
+            r: -self.s(),
+        }
+    }
+
+    /// a sixth of a turn anticlockwise about the origin
+    pub fn rotate_ccw(self) -> Self {
+        Self {
+            q: -self.s(),
+            r: -self.q,
+        }
+    }
+
+    /// `sixths` sixths of a turn clockwise about the origin. Negative turns go anticlockwise
+    pub fn rotate(self, sixths: isize) -> Self {
+        (0..sixths.rem_euclid(6)).fold(self, |hex, _| hex.rotate_cw())
+    }
+
+    /// the hex at `[column, row]` in the given layout
+    pub fn from_offset(pt: Pt<2>, layout: OffsetLayout) -> Self {
+        let [col, row] = pt.0;
+        match layout {
+            OffsetLayout::OddR => Self::new(col - (row - (row & 1)) / 2, row),
+            OffsetLayout::EvenR => Self::new(col - (row + (row & 1)) / 2, row),
+            OffsetLayout::OddQ => Self::new(col, row - (col - (col & 1)) / 2),
+            OffsetLayout::EvenQ => Self::new(col, row - (col + (col & 1)) / 2),
+        }
+    }
+
+    /// this hex as `[column, row]` in the given layout
+    pub fn to_offset(&self, layout: OffsetLayout) -> Pt<2> {
+        let Hex { q, r } = *self;
+        match layout {
+            OffsetLayout::OddR => Pt([q + (r - (r & 1)) / 2, r]),
+            OffsetLayout::EvenR => Pt([q + (r + (r & 1)) / 2, r]),
+            OffsetLayout::OddQ => Pt([q, r + (q - (q & 1)) / 2]),
+            OffsetLayout::EvenQ => Pt([q, r + (q + (q & 1)) / 2]),
+        }
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Hex::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Neg for Hex {
+    type Output = Hex;
+
+    fn neg(self) -> Self::Output {
+        Hex::new(-self.q, -self.r)
+    }
+}
+
+impl Mul<isize> for Hex {
+    type Output = Hex;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        Hex::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+/// A hex grid that can extend infinitely, like `Grid`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexGrid<T: Copy> {
+    default_val: T,
+    pub grid: HashMap<Hex, T>,
+}
+
+impl<T: Default + Copy> Default for HexGrid<T> {
+    fn default() -> Self {
+        Self {
+            default_val: T::default(),
+            grid: Default::default(),
+        }
+    }
+}
+
+impl<T: Default + Copy> From<Vec<(Hex, T)>> for HexGrid<T> {
+    fn from(v: Vec<(Hex, T)>) -> Self {
+        Self {
+            default_val: T::default(),
+            grid: v.into_iter().collect(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Copy> HexGrid<T> {
+    /// get a value at the specified hex or the default
+    pub fn get_def(&self, hex: &Hex) -> T {
+        *self.grid.get(hex).unwrap_or(&self.default_val)
+    }
+
+    /// Change the default value
+    pub fn set_default(&mut self, new_default: T) {
+        self.default_val = new_default;
+    }
+
+    /// the six neighbours of a hex, with their values or the default
+    pub fn neighbours(&self, hex: Hex) -> [(Hex, T); 6] {
+        hex.neighbours()
+            .map(|neighbour| (neighbour, self.get_def(&neighbour)))
+    }
+
+    /// apply a transformation, e.g. a rotation, to every hex in the grid
+    pub fn transform(mut self, transformation: impl Fn(Hex) -> Hex) -> Self {
+        self.grid = self
+            .grid
+            .into_iter()
+            .map(|(hex, v)| (transformation(hex), v))
+            .collect();
+        self
+    }
+
+    /// Print the grid with the hexes staggered like they'd be drawn, using a given function for
+    /// representing values. Each row is shifted half a hex right of the row above it, e.g.
+    /// ```text
+    /// a b c
+    ///  d e f
+    /// ```
+    pub fn print(&self, to_printable: fn(T) -> char) -> String {
+        let mut res = String::from("\n");
+
+        // the text column of a hex is 2q + r
+        let columns = self.grid.keys().map(|hex| 2 * hex.q + hex.r);
+        let rows = self.grid.keys().map(|hex| hex.r);
+        let (Some(min_x), Some(max_x), Some(min_r), Some(max_r)) = (
+            columns.clone().min(),
+            columns.max(),
+            rows.clone().min(),
+            rows.max(),
+        ) else {
+            return res;
+        };
+
+        for r in min_r..max_r + 1 {
+            let mut line = String::new();
+            for x in min_x..max_x + 1 {
+                if (x - r).rem_euclid(2) == 0 {
+                    line.push(to_printable(self.get_def(&Hex::new((x - r) / 2, r))));
+                } else {
+                    line.push(' ');
+                }
+            }
+            res.push_str(line.trim_end());
+            res.push('\n');
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{Hex, HexGrid, OffsetLayout};
+    use crate::utils::point::Pt;
+    use rstest::rstest;
+
+    #[test]
+    fn test_neighbours_are_one_step_away() {
+        let centre = Hex::new(3, -7);
+        let neighbours = centre.neighbours();
+
+        assert_eq!(6, neighbours.iter().collect::<HashSet<_>>().len());
+        for neighbour in neighbours {
+            assert_eq!(1, centre.dist(neighbour));
+        }
+    }
+
+    #[rstest]
+    #[case(Hex::new(0, 0), Hex::new(0, 0), 0)]
+    #[case(Hex::new(0, 0), Hex::new(3, -1), 3)]
+    #[case(Hex::new(-2, 1), Hex::new(1, 2), 4)]
+    #[case(Hex::new(1, -3), Hex::new(-1, 3), 6)]
+    fn validate_dist(#[case] a: Hex, #[case] b: Hex, #[case] expected: isize) {
+        assert_eq!(expected, a.dist(b));
+        assert_eq!(expected, b.dist(a));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let hex = Hex::new(2, -1);
+        assert_eq!(Hex::new(1, 1), hex.rotate_cw());
+        assert_eq!(hex, hex.rotate_cw().rotate_ccw());
+        assert_eq!(hex, hex.rotate(6));
+        assert_eq!(-hex, hex.rotate(3));
+        assert_eq!(hex.rotate_ccw(), hex.rotate(-1));
+
+        // rotating the neighbour offsets steps through them in order
+        for (i, offset) in Hex::NEIGHBOUR_OFFSETS.iter().enumerate() {
+            assert_eq!(Hex::NEIGHBOUR_OFFSETS[(i + 1) % 6], offset.rotate_cw());
+            assert_eq!(
+                hex.dist_from_origin(),
+                hex.rotate(i as isize).dist_from_origin()
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(OffsetLayout::OddR, Pt([1, 1]), Hex::new(1, 1))]
+    #[case(OffsetLayout::EvenR, Pt([1, 1]), Hex::new(0, 1))]
+    #[case(OffsetLayout::OddQ, Pt([1, 1]), Hex::new(1, 1))]
+    #[case(OffsetLayout::EvenQ, Pt([1, 1]), Hex::new(1, 0))]
+    #[case(OffsetLayout::OddR, Pt([-2, -3]), Hex::new(0, -3))]
+    fn validate_offset(#[case] layout: OffsetLayout, #[case] pt: Pt<2>, #[case] hex: Hex) {
+        assert_eq!(hex, Hex::from_offset(pt, layout));
+        assert_eq!(pt, hex.to_offset(layout));
+    }
+
+    #[rstest]
+    #[case(OffsetLayout::OddR)]
+    #[case(OffsetLayout::EvenR)]
+    #[case(OffsetLayout::OddQ)]
+    #[case(OffsetLayout::EvenQ)]
+    fn validate_offset_round_trip(#[case] layout: OffsetLayout) {
+        for col in -4..5 {
+            for row in -4..5 {
+                let pt = Pt([col, row]);
+                assert_eq!(pt, Hex::from_offset(pt, layout).to_offset(layout));
+            }
+        }
+    }
+
+    #[test]
+    fn test_print() {
+        let expected = "
+a b .
+ c d e
+. f g
+";
+
+        let grid = HexGrid::<char>::from(vec![
+            (Hex::new(0, 0), 'a'),
+            (Hex::new(1, 0), 'b'),
+            (Hex::new(0, 1), 'c'),
+            (Hex::new(1, 1), 'd'),
+            (Hex::new(2, 1), 'e'),
+            (Hex::new(0, 2), 'f'),
+            (Hex::new(1, 2), 'g'),
+        ]);
+
+        assert_eq!(expected, grid.print(|c| if c == '\0' { '.' } else { c }));
+    }
+
+    #[test]
+    fn test_neighbours_and_transform() {
+        let grid = HexGrid::<u8>::from(vec![(Hex::new(1, 0), 5), (Hex::new(0, 1), 7)]);
+
+        let neighbours = grid.neighbours(Hex::new(0, 0));
+        assert_eq!((Hex::new(1, 0), 5), neighbours[0]);
+        assert_eq!((Hex::new(0, 1), 7), neighbours[1]);
+        assert_eq!(12, neighbours.iter().map(|(_, v)| v).sum::<u8>());
+
+        let rotated = grid.transform(Hex::rotate_cw);
+        assert_eq!(5, rotated.get_def(&Hex::new(0, 1)));
+        assert_eq!(7, rotated.get_def(&Hex::new(-1, 1)));
+    }
+}
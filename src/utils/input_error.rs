@@ -0,0 +1,197 @@
+use std::{error::Error, fmt::Display};
+
+use super::parser::ParseError;
+
+/// A span of characters on one line of the input. Lines and columns are 1-based
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+}
+
+/// An error in the puzzle input, pointing at where in the input it went wrong.
+/// The runner renders these compiler-style, with the offending line and a caret under the span
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl InputError {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// An error covering the whole of line `line` (1-based) of `input`
+    pub fn at_line(input: &str, line: usize, message: impl Into<String>) -> Self {
+        let len = input
+            .lines()
+            .nth(line.saturating_sub(1))
+            .map(|text| text.chars().count())
+            .unwrap_or(0);
+        Self::new(
+            Span {
+                line,
+                column: 1,
+                len: len.max(1),
+            },
+            message,
+        )
+    }
+
+    /// An error covering `part`, which must be a slice of `input`
+    pub fn at(input: &str, part: &str, message: impl Into<String>) -> Self {
+        let start = (part.as_ptr() as usize)
+            .saturating_sub(input.as_ptr() as usize)
+            .min(input.len());
+        Self::at_offset(input, start, part.chars().count(), message)
+    }
+
+    /// An error covering `len` characters from byte `offset` of `input`
+    pub fn at_offset(input: &str, offset: usize, len: usize, message: impl Into<String>) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Self::new(
+            Span {
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+                len: len.max(1),
+            },
+            message,
+        )
+    }
+
+    /// The same error, for when the text that was parsed started `lines` lines into the input
+    pub fn shift_lines(mut self, lines: usize) -> Self {
+        self.span.line += lines;
+        self
+    }
+
+    /// Find an input error in an error chain, including parser errors
+    pub fn find(err: &anyhow::Error) -> Option<InputError> {
+        err.chain().find_map(|cause| {
+            cause
+                .downcast_ref::<InputError>()
+                .cloned()
+                .or_else(|| cause.downcast_ref::<ParseError>().cloned().map(Self::from))
+        })
+    }
+
+    /// Render the error compiler-style, quoting the offending line of `input`
+    pub fn render(&self, file_name: &str, input: &str) -> String {
+        let line = input
+            .lines()
+            .nth(self.span.line.saturating_sub(1))
+            .unwrap_or("");
+        let gutter = " ".repeat(self.span.line.to_string().len());
+
+        format!(
+            "{}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}",
+            self.message,
+            gutter,
+            file_name,
+            self.span.line,
+            self.span.column,
+            gutter,
+            self.span.line,
+            line,
+            gutter,
+            " ".repeat(self.span.column.saturating_sub(1)),
+            "^".repeat(self.span.len)
+        )
+    }
+}
+
+impl From<ParseError> for InputError {
+    fn from(err: ParseError) -> Self {
+        Self::new(
+            Span {
+                line: err.line,
+                column: err.column,
+                len: 1,
+            },
+            format!("expected {}, found {}", err.expected, err.found),
+        )
+    }
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+impl Error for InputError {}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Context};
+
+    use super::{InputError, Span};
+    use crate::utils::parser::{int, parse_all};
+
+    const INPUT: &str = "$ cd /\n$ ls\nfoo bar\n";
+
+    #[test]
+    fn test_at_slice() {
+        let line = INPUT.lines().nth(2).unwrap();
+        let err = InputError::at(INPUT, &line[4..], "bad name");
+
+        assert_eq!(
+            Span {
+                line: 3,
+                column: 5,
+                len: 3
+            },
+            err.span
+        );
+        assert_eq!("line 3, column 5: bad name", err.to_string());
+    }
+
+    #[test]
+    fn test_render() {
+        let err = InputError::at_line(INPUT, 3, "Unknown command 'foo'");
+
+        assert_eq!(
+            "Unknown command 'foo'
+ --> input_07.txt:3:1
+  |
+3 | foo bar
+  | ^^^^^^^",
+            err.render("input_07.txt", INPUT)
+        );
+    }
+
+    #[test]
+    fn test_find_in_chain() {
+        let parse_err = parse_all(&int::<u32>(), "12x").unwrap_err();
+        let err = anyhow!(parse_err).context("loading");
+
+        assert_eq!(
+            Some(InputError::new(
+                Span {
+                    line: 1,
+                    column: 3,
+                    len: 1
+                },
+                "expected end of input, found 'x'"
+            )),
+            InputError::find(&err)
+        );
+        assert_eq!(None, InputError::find(&anyhow!("unrelated")));
+
+        let wrapped = Err::<(), _>(InputError::at_line(INPUT, 1, "bad")).context("day 7");
+        assert_eq!(
+            1,
+            InputError::find(&wrapped.unwrap_err()).unwrap().span.line
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! Diamond-shaped regions: every point within a Manhattan distance of a centre, like a sensor's
+//! coverage in day 15.
+//!
+//! The 2D geometry works in rotated coordinates `u = x + y` and `v = x - y`, where a ball is an
+//! axis-aligned square. A lattice point `(x, y)` is any `(u, v)` where `u` and `v` are both even or
+//! both odd, and back again with `x = (u + v) / 2` and `y = (u - v) / 2`.
+
+use itertools::Itertools;
+
+use super::point::{Metric, Pt};
+
+/// Every point within `radius` of `centre`, by Manhattan distance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ManhattanBall<const DIMS: usize> {
+    pub centre: Pt<DIMS>,
+    pub radius: isize,
+}
+
+#[allow(dead_code)]
+impl<const DIMS: usize> ManhattanBall<DIMS> {
+    pub fn new(centre: Pt<DIMS>, radius: isize) -> Self {
+        Self { centre, radius }
+    }
+
+    /// the smallest ball around `centre` that reaches `edge`, e.g. a sensor and its closest beacon
+    pub fn reaching(centre: Pt<DIMS>, edge: Pt<DIMS>) -> Self {
+        Self::new(centre, centre.dist(edge, Metric::Manhattan))
+    }
+
+    pub fn contains(&self, pt: Pt<DIMS>) -> bool {
+        self.centre.dist(pt, Metric::Manhattan) <= self.radius
+    }
+
+    /// the same ball with the radius changed by `by`, e.g. `grow(1)` for the ring just outside
+    pub fn grow(&self, by: isize) -> Self {
+        Self::new(self.centre, self.radius + by)
+    }
+
+    /// the minimum and maximum values in each dimension
+    pub fn bounds(&self) -> (Pt<DIMS>, Pt<DIMS>) {
+        let extent = Pt([self.radius; DIMS]);
+        (self.centre - extent, self.centre + extent)
+    }
+
+    /// whether any point in the box from `min` to `max` (inclusive) is in the ball
+    pub fn overlaps_box(&self, min: Pt<DIMS>, max: Pt<DIMS>) -> bool {
+        self.contains(self.centre.component_max(min).component_min(max))
+    }
+}
+
+#[allow(dead_code)]
+impl ManhattanBall<2> {
+    /// the points of row `y` in the ball, as an inclusive range of x
+    pub fn row_span(&self, y: isize) -> Option<(isize, isize)> {
+        let Pt([x, centre_y]) = self.centre;
+        let half_width = self.radius - (y - centre_y).abs();
+        (half_width >= 0).then(|| (x - half_width, x + half_width))
+    }
+
+    /// the points of row `y` in the ball that are also between `min_x` and `max_x`
+    pub fn clip_row(&self, y: isize, min_x: isize, max_x: isize) -> Option<(isize, isize)> {
+        let (from, to) = self.row_span(y)?;
+        let (from, to) = (from.max(min_x), to.min(max_x));
+        (from <= to).then_some((from, to))
+    }
+
+    /// the points exactly `radius` from the centre, going clockwise from the rightmost point.
+    /// `grow(1).boundary()` is the ring just outside the ball
+    pub fn boundary(&self) -> impl Iterator<Item = Pt<2>> {
+        let ManhattanBall { centre, radius } = *self;
+        let sides = [Pt([-1, 1]), Pt([-1, -1]), Pt([1, -1]), Pt([1, 1])];
+        let corners = [
+            Pt([radius, 0]),
+            Pt([0, radius]),
+            Pt([-radius, 0]),
+            Pt([0, -radius]),
+        ];
+
+        let points = (0..4)
+            .flat_map(move |side| (0..radius).map(move |i| corners[side] + sides[side] * i))
+            .map(move |offset| centre + offset);
+        // a ball of radius 0 is just its centre
+        (radius == 0).then_some(centre).into_iter().chain(points)
+    }
+
+    /// the lattice points on both boundaries: where they cross, and the ends of any stretch
+    /// where they run along each other
+    pub fn boundary_intersections(&self, other: &ManhattanBall<2>) -> Vec<Pt<2>> {
+        let mut found = Vec::new();
+
+        for (a, b) in self.edges().into_iter().cartesian_product(other.edges()) {
+            match (a, b) {
+                // an edge along u meets one along v at a single point, if at all
+                (Edge::U { u, v_from, v_to }, Edge::V { v, u_from, u_to })
+                | (Edge::V { v, u_from, u_to }, Edge::U { u, v_from, v_to })
+                    if (v_from..=v_to).contains(&v) && (u_from..=u_to).contains(&u) =>
+                {
+                    found.push((u, v));
+                }
+                // parallel edges on the same line meet at the ends of their overlap
+                (
+                    Edge::U {
+                        u: a_u,
+                        v_from: a_from,
+                        v_to: a_to,
+                    },
+                    Edge::U {
+                        u: b_u,
+                        v_from: b_from,
+                        v_to: b_to,
+                    },
+                ) if a_u == b_u => {
+                    let (from, to) = (a_from.max(b_from), a_to.min(b_to));
+                    if from <= to {
+                        found.extend([(a_u, from), (a_u, to)]);
+                    }
+                }
+                (
+                    Edge::V {
+                        v: a_v,
+                        u_from: a_from,
+                        u_to: a_to,
+                    },
+                    Edge::V {
+                        v: b_v,
+                        u_from: b_from,
+                        u_to: b_to,
+                    },
+                ) if a_v == b_v => {
+                    let (from, to) = (a_from.max(b_from), a_to.min(b_to));
+                    if from <= to {
+                        found.extend([(from, a_v), (to, a_v)]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        found
+            .into_iter()
+            .filter_map(|(u, v)| from_uv(u, v))
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// the number of points in at least one of the balls
+    pub fn union_area(balls: &[ManhattanBall<2>]) -> isize {
+        // split the u/v plane up wherever a ball starts or ends, so every cell is either
+        // entirely inside or entirely outside each ball
+        let cuts = |axis: fn(&ManhattanBall<2>) -> isize| {
+            balls
+                .iter()
+                .flat_map(|ball| [axis(ball) - ball.radius, axis(ball) + ball.radius + 1])
+                .sorted()
+                .dedup()
+                .collect_vec()
+        };
+        let us = cuts(|ball| ball.centre.0[0] + ball.centre.0[1]);
+        let vs = cuts(|ball| ball.centre.0[0] - ball.centre.0[1]);
+
+        let mut area = 0;
+        for (u_from, u_to) in us.iter().tuple_windows() {
+            for (v_from, v_to) in vs.iter().tuple_windows() {
+                let covered = balls.iter().any(|ball| {
+                    let [x, y] = ball.centre.0;
+                    (*u_from - (x + y)).abs() <= ball.radius
+                        && (*v_from - (x - y)).abs() <= ball.radius
+                });
+                if covered {
+                    // only the (u, v) with matching parity are lattice points
+                    let (u_even, v_even) = (evens(*u_from, *u_to), evens(*v_from, *v_to));
+                    let (u_odd, v_odd) = (u_to - u_from - u_even, v_to - v_from - v_even);
+                    area += u_even * v_even + u_odd * v_odd;
+                }
+            }
+        }
+        area
+    }
+
+    /// the four sides of the square the ball is in the u/v plane
+    fn edges(&self) -> [Edge; 4] {
+        let [x, y] = self.centre.0;
+        let (u, v, r) = (x + y, x - y, self.radius);
+        [
+            Edge::U {
+                u: u - r,
+                v_from: v - r,
+                v_to: v + r,
+            },
+            Edge::U {
+                u: u + r,
+                v_from: v - r,
+                v_to: v + r,
+            },
+            Edge::V {
+                v: v - r,
+                u_from: u - r,
+                u_to: u + r,
+            },
+            Edge::V {
+                v: v + r,
+                u_from: u - r,
+                u_to: u + r,
+            },
+        ]
+    }
+}
+
+/// A side of a ball in the u/v plane, where u or v is fixed
+#[derive(Debug, Clone, Copy)]
+enum Edge {
+    U {
+        u: isize,
+        v_from: isize,
+        v_to: isize,
+    },
+    V {
+        v: isize,
+        u_from: isize,
+        u_to: isize,
+    },
+}
+
+/// the point at `(u, v)`, if it's a lattice point
+fn from_uv(u: isize, v: isize) -> Option<Pt<2>> {
+    ((u - v).rem_euclid(2) == 0).then(|| Pt([(u + v) / 2, (u - v) / 2]))
+}
+
+/// the number of even numbers in `from..to`
+fn evens(from: isize, to: isize) -> isize {
+    (to + 1).div_euclid(2) - (from + 1).div_euclid(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use itertools::Itertools;
+    use rstest::rstest;
+
+    use super::ManhattanBall;
+    use crate::utils::{
+        point::{Metric, Pt},
+        property::{check, DEFAULT_CASES},
+    };
+
+    #[test]
+    fn test_contains_and_bounds() {
+        let ball = ManhattanBall::reaching(Pt([8, 7]), Pt([2, 10]));
+        assert_eq!(9, ball.radius);
+        assert!(ball.contains(Pt([8, -2])));
+        assert!(!ball.contains(Pt([9, -2])));
+        assert_eq!((Pt([-1, -2]), Pt([17, 16])), ball.bounds());
+
+        assert!(ball.overlaps_box(Pt([12, 12]), Pt([20, 20])));
+        assert!(!ball.overlaps_box(Pt([13, 13]), Pt([20, 20])));
+        assert!(ball.overlaps_box(Pt([0, 5]), Pt([1, 6])));
+        assert!(!ball.overlaps_box(Pt([0, 0]), Pt([1, 1])));
+    }
+
+    #[rstest]
+    #[case(7, Some((-1, 17)))]
+    #[case(10, Some((2, 14)))]
+    #[case(16, Some((8, 8)))]
+    #[case(17, None)]
+    fn validate_row_span(#[case] y: isize, #[case] expected: Option<(isize, isize)>) {
+        let ball = ManhattanBall::new(Pt([8, 7]), 9);
+        assert_eq!(expected, ball.row_span(y));
+    }
+
+    #[test]
+    fn test_clip_row() {
+        let ball = ManhattanBall::new(Pt([8, 7]), 9);
+        assert_eq!(Some((2, 5)), ball.clip_row(10, 0, 5));
+        assert_eq!(None, ball.clip_row(10, 15, 20));
+    }
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(1, 4)]
+    #[case(5, 20)]
+    fn validate_boundary(#[case] radius: isize, #[case] expected_len: usize) {
+        let ball = ManhattanBall::new(Pt([-3, 4]), radius);
+        let boundary = ball.boundary().collect_vec();
+
+        assert_eq!(expected_len, boundary.len());
+        assert_eq!(expected_len, boundary.iter().collect::<HashSet<_>>().len());
+        assert!(boundary
+            .iter()
+            .all(|pt| ball.centre.dist(*pt, Metric::Manhattan) == radius));
+    }
+
+    #[rstest]
+    #[case::crossing(ManhattanBall::new(Pt([0, 0]), 2), ManhattanBall::new(Pt([2, 0]), 2), vec![[1, -1], [1, 1]])]
+    #[case::touching(ManhattanBall::new(Pt([0, 0]), 1), ManhattanBall::new(Pt([3, 0]), 2), vec![[1, 0]])]
+    #[case::apart(ManhattanBall::new(Pt([0, 0]), 1), ManhattanBall::new(Pt([5, 0]), 1), vec![])]
+    #[case::inside(ManhattanBall::new(Pt([0, 0]), 5), ManhattanBall::new(Pt([1, 0]), 1), vec![])]
+    #[case::off_lattice(ManhattanBall::new(Pt([0, 0]), 1), ManhattanBall::new(Pt([1, 0]), 1), vec![])]
+    #[case::along_a_side(ManhattanBall::new(Pt([0, 0]), 2), ManhattanBall::new(Pt([3, 1]), 2), vec![[1, 1], [2, 0]])]
+    fn validate_boundary_intersections(
+        #[case] a: ManhattanBall<2>,
+        #[case] b: ManhattanBall<2>,
+        #[case] expected: Vec<[isize; 2]>,
+    ) {
+        let expected = expected.into_iter().map(Pt).collect_vec();
+        assert_eq!(expected, a.boundary_intersections(&b));
+        assert_eq!(expected, b.boundary_intersections(&a));
+    }
+
+    #[test]
+    fn boundary_intersections_match_brute_force() {
+        check(
+            DEFAULT_CASES,
+            41,
+            |rng| {
+                (
+                    (rng.range(-6, 6), rng.range(-6, 6), rng.range(0, 5)),
+                    (rng.range(-6, 6), rng.range(-6, 6), rng.range(0, 5)),
+                )
+            },
+            |((ax, ay, ar), (bx, by, br))| {
+                let a = ManhattanBall::new(Pt([*ax as isize, *ay as isize]), *ar as isize);
+                let b = ManhattanBall::new(Pt([*bx as isize, *by as isize]), *br as isize);
+
+                let found = a.boundary_intersections(&b);
+                let on_both = a
+                    .boundary()
+                    .filter(|pt| b.boundary().contains(pt))
+                    .collect::<HashSet<_>>();
+
+                // every point found is on both, and every run of shared points has its ends found
+                if let Some(pt) = found.iter().find(|pt| !on_both.contains(pt)) {
+                    return Err(format!("{:?} is not on both boundaries", pt));
+                }
+                let is_end = |pt: &Pt<2>| {
+                    Pt::<2>::neighbour_offsets()
+                        .iter()
+                        .filter(|offset| on_both.contains(&(*pt + **offset)))
+                        .count()
+                        < 2
+                };
+                if let Some(pt) = on_both.iter().find(|pt| is_end(pt) && !found.contains(pt)) {
+                    return Err(format!("missed {:?}, found {:?}", pt, found));
+                }
+                Ok(())
+            },
+        );
+    }
+
+    #[test]
+    fn union_area_matches_brute_force() {
+        check(
+            DEFAULT_CASES,
+            42,
+            |rng| {
+                rng.vec(0, 5, |rng| {
+                    (rng.range(-8, 8), rng.range(-8, 8), rng.range(0, 6))
+                })
+            },
+            |balls| {
+                let balls = balls
+                    .iter()
+                    .map(|(x, y, r)| {
+                        ManhattanBall::new(Pt([*x as isize, *y as isize]), *r as isize)
+                    })
+                    .collect_vec();
+
+                let expected = (-15..=15)
+                    .cartesian_product(-15..=15)
+                    .filter(|(x, y)| balls.iter().any(|ball| ball.contains(Pt([*x, *y]))))
+                    .count() as isize;
+                let area = ManhattanBall::union_area(&balls);
+
+                if area != expected {
+                    return Err(format!("got an area of {}, expected {}", area, expected));
+                }
+                Ok(())
+            },
+        );
+    }
+}
@@ -1,4 +1,21 @@
+pub mod cellular_automaton;
+pub mod checked;
+pub mod dense_grid;
+pub mod direction;
+pub mod examples;
+pub mod from_line;
 pub mod grid;
+pub mod hex;
+pub mod input_error;
 pub mod load_input;
+pub mod manhattan_ball;
+pub mod orientation;
+pub mod parser;
+pub mod pathfinding;
 pub mod point;
+pub mod progress;
+#[cfg(test)]
+pub mod property;
+pub mod regions;
+pub mod simulation;
 pub mod solver_types;
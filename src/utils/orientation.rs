@@ -0,0 +1,250 @@
+//! Rotations and reflections of points and grids, for puzzles where things need lining up
+//! (scanner alignment) or folding (cubes).
+//!
+//! An `Orientation` maps each axis onto an axis, possibly flipped, so it covers every rotation by
+//! multiples of 90°, and the reflections too. Rotations are about the origin.
+//!
+//! In 2D, y points down the screen like in `Grid::print`, so clockwise is as it looks when printed.
+
+use std::array;
+
+use itertools::Itertools;
+
+use super::point::{Pt, Scalar};
+
+/// A rotation, or a rotation combined with a reflection, of `DIMS` dimensional space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Orientation<const DIMS: usize> {
+    /// the axis of the original point that ends up along each axis
+    axes: [usize; DIMS],
+    /// whether each axis of the result is flipped, as 1 or -1
+    signs: [isize; DIMS],
+}
+
+#[allow(dead_code)]
+impl<const DIMS: usize> Orientation<DIMS> {
+    /// Leaves everything where it is
+    pub fn identity() -> Self {
+        Self {
+            axes: array::from_fn(|i| i),
+            signs: [1; DIMS],
+        }
+    }
+
+    /// A quarter turn that takes the `from` axis onto the `to` axis,
+    /// e.g. `quarter_turn(0, 1)` takes x onto y, which is clockwise in 2D
+    pub fn quarter_turn(from: usize, to: usize) -> Self {
+        assert!(from != to && from < DIMS && to < DIMS, "invalid axes");
+        let mut orientation = Self::identity();
+        orientation.axes[to] = from;
+        orientation.axes[from] = to;
+        orientation.signs[from] = -1;
+        orientation
+    }
+
+    /// Flips the given axis
+    pub fn reflection(axis: usize) -> Self {
+        let mut orientation = Self::identity();
+        orientation.signs[axis] = -1;
+        orientation
+    }
+
+    /// Every rotation: 4 in 2D, and 24 in 3D
+    pub fn all() -> Vec<Self> {
+        Self::all_with_reflections()
+            .into_iter()
+            .filter(|orientation| !orientation.is_reflection())
+            .collect()
+    }
+
+    /// Every rotation, and every rotation combined with a reflection: 8 in 2D, and 48 in 3D
+    pub fn all_with_reflections() -> Vec<Self> {
+        let sign_choices = vec![[1, -1]; DIMS].into_iter().multi_cartesian_product();
+
+        (0..DIMS)
+            .permutations(DIMS)
+            .cartesian_product(sign_choices.collect_vec())
+            .map(|(axes, signs)| Self {
+                axes: axes.try_into().unwrap(),
+                signs: signs.try_into().unwrap(),
+            })
+            .collect()
+    }
+
+    /// Whether this turns space inside out, i.e. whether it includes a reflection
+    pub fn is_reflection(&self) -> bool {
+        let inversions = (0..DIMS)
+            .tuple_combinations()
+            .filter(|&(i, j)| self.axes[i] > self.axes[j])
+            .count();
+        let flips = self.signs.iter().filter(|sign| **sign < 0).count();
+        (inversions + flips) % 2 == 1
+    }
+
+    pub fn apply<T: Scalar>(&self, pt: Pt<DIMS, T>) -> Pt<DIMS, T> {
+        Pt(array::from_fn(|i| match self.signs[i] {
+            -1 => -pt.0[self.axes[i]],
+            _ => pt.0[self.axes[i]],
+        }))
+    }
+
+    /// This orientation followed by `next`
+    pub fn then(&self, next: Orientation<DIMS>) -> Self {
+        Self {
+            axes: array::from_fn(|i| self.axes[next.axes[i]]),
+            signs: array::from_fn(|i| next.signs[i] * self.signs[next.axes[i]]),
+        }
+    }
+
+    /// The orientation that undoes this one
+    pub fn inverse(&self) -> Self {
+        let mut inverse = Self::identity();
+        for i in 0..DIMS {
+            inverse.axes[self.axes[i]] = i;
+            inverse.signs[self.axes[i]] = self.signs[i];
+        }
+        inverse
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Scalar> Pt<2, T> {
+    /// a quarter turn clockwise about the origin
+    pub fn rotate_cw(self) -> Self {
+        Orientation::quarter_turn(0, 1).apply(self)
+    }
+
+    /// a quarter turn anticlockwise about the origin
+    pub fn rotate_ccw(self) -> Self {
+        Orientation::quarter_turn(1, 0).apply(self)
+    }
+
+    /// a half turn about the origin
+    pub fn rotate_180(self) -> Self {
+        -self
+    }
+
+    /// mirrored left to right, in the y axis
+    pub fn flip_horizontal(self) -> Self {
+        Orientation::reflection(0).apply(self)
+    }
+
+    /// mirrored top to bottom, in the x axis
+    pub fn flip_vertical(self) -> Self {
+        Orientation::reflection(1).apply(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use itertools::Itertools;
+    use rstest::rstest;
+
+    use super::Orientation;
+    use crate::utils::point::Pt;
+
+    #[rstest]
+    #[case(Pt([2, -1]), Pt([1, 2]), Pt([-2, 1]), Pt([-1, -2]))]
+    #[case(Pt([0, 1]), Pt([-1, 0]), Pt([0, -1]), Pt([1, 0]))]
+    fn validate_2d_rotations(
+        #[case] pt: Pt<2>,
+        #[case] cw: Pt<2>,
+        #[case] half: Pt<2>,
+        #[case] ccw: Pt<2>,
+    ) {
+        assert_eq!(cw, pt.rotate_cw());
+        assert_eq!(half, pt.rotate_180());
+        assert_eq!(half, pt.rotate_cw().rotate_cw());
+        assert_eq!(ccw, pt.rotate_ccw());
+        assert_eq!(pt, pt.rotate_cw().rotate_ccw());
+    }
+
+    #[test]
+    fn test_2d_reflections() {
+        assert_eq!(Pt([-2, 1]), Pt([2, 1]).flip_horizontal());
+        assert_eq!(Pt([2, -1]), Pt([2, 1]).flip_vertical());
+        assert_eq!(
+            Pt([2, 1]).rotate_180(),
+            Pt([2, 1]).flip_horizontal().flip_vertical()
+        );
+    }
+
+    #[rstest]
+    #[case::two_d(2, 4, 8)]
+    #[case::three_d(3, 24, 48)]
+    fn validate_counts(
+        #[case] dims: usize,
+        #[case] rotations: usize,
+        #[case] with_reflections: usize,
+    ) {
+        fn counts<const DIMS: usize>() -> (usize, usize) {
+            let all = Orientation::<DIMS>::all()
+                .into_iter()
+                .collect::<HashSet<_>>();
+            let with_reflections = Orientation::<DIMS>::all_with_reflections()
+                .into_iter()
+                .collect::<HashSet<_>>();
+            (all.len(), with_reflections.len())
+        }
+
+        let actual = match dims {
+            2 => counts::<2>(),
+            _ => counts::<3>(),
+        };
+        assert_eq!((rotations, with_reflections), actual);
+    }
+
+    #[test]
+    fn test_3d_rotations_are_distinct_and_keep_handedness() {
+        let pt = Pt([1, 2, 3]);
+        let rotated = Orientation::<3>::all()
+            .iter()
+            .map(|orientation| orientation.apply(pt))
+            .collect::<HashSet<_>>();
+        assert_eq!(24, rotated.len());
+
+        // x cross y = z for the rotated axes
+        let [x, y, z] = [Pt([1, 0, 0]), Pt([0, 1, 0]), Pt([0, 0, 1])];
+        for orientation in Orientation::<3>::all() {
+            let [a, b, c] = [x, y, z].map(|axis| orientation.apply(axis).0);
+            let cross = [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ];
+            assert_eq!(c, cross, "{:?} is not a rotation", orientation);
+        }
+    }
+
+    #[test]
+    fn test_compose_and_invert() {
+        let pt = Pt([4, -5, 6]);
+        let all = Orientation::<3>::all();
+
+        for (a, b) in all.iter().cartesian_product(all.iter()) {
+            assert_eq!(b.apply(a.apply(pt)), a.then(*b).apply(pt));
+            assert!(all.contains(&a.then(*b)), "rotations should be closed");
+        }
+        for orientation in Orientation::<3>::all_with_reflections() {
+            assert_eq!(pt, orientation.inverse().apply(orientation.apply(pt)));
+            assert_eq!(
+                Orientation::identity(),
+                orientation.then(orientation.inverse())
+            );
+        }
+    }
+
+    #[test]
+    fn test_quarter_turns() {
+        let about_z = Orientation::<3>::quarter_turn(0, 1);
+        assert_eq!(Pt([0, 1, 7]), about_z.apply(Pt([1, 0, 7])));
+        assert_eq!(
+            Orientation::identity(),
+            (0..4).fold(Orientation::identity(), |acc, _| acc.then(about_z))
+        );
+        assert!(Orientation::<3>::reflection(2).is_reflection());
+        assert!(!about_z.is_reflection());
+    }
+}
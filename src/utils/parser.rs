@@ -0,0 +1,620 @@
+//! A small parser combinator library.
+//! Parsers are functions from an `Input` to the parsed value and the remaining input.
+//! They never panic, and failures report the line and column where parsing went wrong and what was expected.
+
+use std::{any::type_name, cell::Cell, error::Error, fmt::Display, str::FromStr};
+
+/// The text being parsed, and how far into it a parser has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Input<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self { text, offset: 0 }
+    }
+
+    /// the text that hasn't been parsed yet
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.offset..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset >= self.text.len()
+    }
+
+    /// the input after skipping `len` bytes
+    fn advance(&self, len: usize) -> Self {
+        Self {
+            text: self.text,
+            offset: self.offset + len,
+        }
+    }
+
+    /// the 1-based line and column of the current position
+    pub fn position(&self) -> (usize, usize) {
+        let before = &self.text[..self.offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+        (line, column)
+    }
+
+    /// fail at the current position
+    pub fn error<T>(&self, expected: impl Into<String>) -> ParseResult<'a, T> {
+        Err(ParseError::at(*self, expected))
+    }
+}
+
+/// Where and why parsing failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number, in characters
+    pub column: usize,
+    /// what the parser was looking for
+    pub expected: String,
+    /// what it found instead
+    pub found: String,
+    /// byte offset into the input, used to decide which of several failed alternatives got furthest
+    offset: usize,
+}
+
+impl ParseError {
+    fn at(input: Input, expected: impl Into<String>) -> Self {
+        let (line, column) = input.position();
+        let found = match input.rest().chars().next() {
+            None => "end of input".to_string(),
+            Some('\n') => "end of line".to_string(),
+            Some(c) => format!("'{}'", c),
+        };
+        Self {
+            line,
+            column,
+            expected: expected.into(),
+            found,
+            offset: input.offset,
+        }
+    }
+
+    /// combine the errors from two alternatives. The one that got further wins, or both are expected
+    fn or(self, other: ParseError) -> ParseError {
+        match self.offset.cmp(&other.offset) {
+            std::cmp::Ordering::Greater => self,
+            std::cmp::Ordering::Less => other,
+            std::cmp::Ordering::Equal => ParseError {
+                expected: format!("{} or {}", self.expected, other.expected),
+                ..self
+            },
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found {}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// The parsed value and the remaining input, or where parsing failed
+pub type ParseResult<'a, T> = Result<(T, Input<'a>), ParseError>;
+
+/// Anything that can parse a `T` from the start of an input.
+/// Implemented for functions and closures, so recursive grammars can be written as plain functions
+pub trait Parser<'a, T> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T>;
+
+    /// transform the parsed value
+    fn map<U, F: Fn(T) -> U>(self, f: F) -> Map<Self, F, T>
+    where
+        Self: Sized,
+    {
+        Map {
+            parser: self,
+            f,
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// transform the parsed value with a function that can fail, failing with `expected` at the start of the value
+    fn try_map<U, F: Fn(T) -> Option<U>>(self, expected: &'static str, f: F) -> TryMap<Self, F, T>
+    where
+        Self: Sized,
+    {
+        TryMap {
+            parser: self,
+            f,
+            expected,
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// parse this, then `next`, keeping both values
+    fn and<U, P: Parser<'a, U>>(self, next: P) -> And<Self, P>
+    where
+        Self: Sized,
+    {
+        And { first: self, next }
+    }
+
+    /// parse this, then `next`, keeping only this value
+    fn skip<U, P: Parser<'a, U>>(self, next: P) -> Skip<Self, P, U>
+    where
+        Self: Sized,
+    {
+        Skip {
+            parser: self,
+            skipped: next,
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// parse this, or `other` if this fails
+    fn or<P: Parser<'a, T>>(self, other: P) -> Or<Self, P>
+    where
+        Self: Sized,
+    {
+        Or { first: self, other }
+    }
+}
+
+impl<'a, T, F: Fn(Input<'a>) -> ParseResult<'a, T>> Parser<'a, T> for F {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+pub struct Map<P, F, T> {
+    parser: P,
+    f: F,
+    marker: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<'a, T, U, P: Parser<'a, T>, F: Fn(T) -> U> Parser<'a, U> for Map<P, F, T> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, U> {
+        let (value, rest) = self.parser.parse(input)?;
+        Ok(((self.f)(value), rest))
+    }
+}
+
+pub struct TryMap<P, F, T> {
+    parser: P,
+    f: F,
+    expected: &'static str,
+    marker: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<'a, T, U, P: Parser<'a, T>, F: Fn(T) -> Option<U>> Parser<'a, U> for TryMap<P, F, T> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, U> {
+        let (value, rest) = self.parser.parse(input)?;
+        match (self.f)(value) {
+            Some(value) => Ok((value, rest)),
+            None => input.error(self.expected),
+        }
+    }
+}
+
+pub struct And<P, Q> {
+    first: P,
+    next: Q,
+}
+
+impl<'a, T, U, P: Parser<'a, T>, Q: Parser<'a, U>> Parser<'a, (T, U)> for And<P, Q> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, (T, U)> {
+        let (first, rest) = self.first.parse(input)?;
+        let (next, rest) = self.next.parse(rest)?;
+        Ok(((first, next), rest))
+    }
+}
+
+pub struct Skip<P, Q, U> {
+    parser: P,
+    skipped: Q,
+    marker: std::marker::PhantomData<fn() -> U>,
+}
+
+impl<'a, T, U, P: Parser<'a, T>, Q: Parser<'a, U>> Parser<'a, T> for Skip<P, Q, U> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T> {
+        let (value, rest) = self.parser.parse(input)?;
+        let (_, rest) = self.skipped.parse(rest)?;
+        Ok((value, rest))
+    }
+}
+
+pub struct Or<P, Q> {
+    first: P,
+    other: Q,
+}
+
+impl<'a, T, P: Parser<'a, T>, Q: Parser<'a, T>> Parser<'a, T> for Or<P, Q> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T> {
+        match self.first.parse(input) {
+            Ok(result) => Ok(result),
+            Err(first_err) => self
+                .other
+                .parse(input)
+                .map_err(|other_err| first_err.or(other_err)),
+        }
+    }
+}
+
+/// Run a parser over the whole of `text`. Trailing whitespace is allowed, anything else left over is an error
+pub fn parse_all<'a, T>(parser: &impl Parser<'a, T>, text: &'a str) -> Result<T, ParseError> {
+    let (value, rest) = parser.parse(Input::new(text))?;
+    let rest = rest.advance(rest.rest().len() - rest.rest().trim_start().len());
+    if !rest.is_empty() {
+        return Err(ParseError::at(rest, "end of input"));
+    }
+    Ok(value)
+}
+
+/// Exactly the text `expected`
+pub fn literal<'a>(expected: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        if input.rest().starts_with(expected) {
+            Ok((
+                &input.rest()[..expected.len()],
+                input.advance(expected.len()),
+            ))
+        } else {
+            input.error(format!("'{}'", expected))
+        }
+    }
+}
+
+/// The longest (possibly empty) run of characters matching `predicate`
+pub fn take_while<'a>(predicate: impl Fn(char) -> bool) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        let len = input
+            .rest()
+            .find(|c: char| !predicate(c))
+            .unwrap_or(input.rest().len());
+        Ok((&input.rest()[..len], input.advance(len)))
+    }
+}
+
+/// A non-empty run of characters matching `predicate`
+pub fn take_while1<'a>(
+    expected: &'static str,
+    predicate: impl Fn(char) -> bool,
+) -> impl Parser<'a, &'a str> {
+    let take = take_while(predicate);
+    move |input: Input<'a>| match take.parse(input)? {
+        ("", _) => input.error(expected),
+        result => Ok(result),
+    }
+}
+
+/// Any single character
+pub fn any_char<'a>() -> impl Parser<'a, char> {
+    |input: Input<'a>| match input.rest().chars().next() {
+        Some(c) => Ok((c, input.advance(c.len_utf8()))),
+        None => input.error("a character"),
+    }
+}
+
+/// A non-empty word, made of anything but whitespace
+pub fn word<'a>() -> impl Parser<'a, &'a str> {
+    take_while1("a word", |c| !c.is_whitespace())
+}
+
+/// Everything up to the end of the line, not including the line ending
+pub fn rest_of_line<'a>() -> impl Parser<'a, &'a str> {
+    take_while(|c| c != '\n' && c != '\r')
+}
+
+/// A single `\n` or `\r\n`
+pub fn line_ending<'a>() -> impl Parser<'a, &'a str> {
+    literal("\n").or(literal("\r\n"))
+}
+
+/// Parse a run of characters with `FromStr`, failing at its start if it doesn't fit in a `T`
+fn from_digits<'a, T: FromStr>(
+    expected: &'static str,
+    digits: impl Parser<'a, &'a str>,
+) -> impl Parser<'a, T> {
+    move |input: Input<'a>| {
+        let (text, rest) = digits.parse(input)?;
+        match text.parse::<T>() {
+            Ok(value) => Ok((value, rest)),
+            Err(_) if !text.is_empty() && text != "-" => {
+                input.error(format!("a number that fits in {}", type_name::<T>()))
+            }
+            Err(_) => input.error(expected),
+        }
+    }
+}
+
+/// An unsigned integer, e.g. `42`
+pub fn int<'a, T: FromStr>() -> impl Parser<'a, T> {
+    from_digits("an integer", take_while(|c| c.is_ascii_digit()))
+}
+
+/// An integer with an optional leading `-`, e.g. `-42`
+pub fn signed<'a, T: FromStr>() -> impl Parser<'a, T> {
+    from_digits("an integer", |input: Input<'a>| {
+        let minus = usize::from(input.rest().starts_with('-'));
+        let digits = input.rest()[minus..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.rest().len() - minus);
+        Ok((
+            &input.rest()[..minus + digits],
+            input.advance(minus + digits),
+        ))
+    })
+}
+
+/// Zero or more `item`s separated by `separator`, e.g. `1,2,3`.
+/// The list ends where an item can't even start, so `1,2,` parses as `[1, 2]` leaving the `,`.
+/// An item that fails partway through fails the whole list
+pub fn separated<'a, T, U>(
+    item: impl Parser<'a, T>,
+    separator: impl Parser<'a, U>,
+) -> impl Parser<'a, Vec<T>> {
+    move |input: Input<'a>| {
+        let mut items = Vec::new();
+        let mut rest = input;
+        loop {
+            let after_separator = if items.is_empty() {
+                rest
+            } else {
+                match separator.parse(rest) {
+                    Ok((_, after_separator)) => after_separator,
+                    Err(_) => break,
+                }
+            };
+
+            match item.parse(after_separator) {
+                Ok((next, after_item)) => {
+                    // stop rather than loop forever if neither the separator nor the item consume anything
+                    let stuck = !items.is_empty() && after_item.offset == rest.offset;
+                    items.push(next);
+                    rest = after_item;
+                    if stuck {
+                        break;
+                    }
+                }
+                Err(err) if err.offset > after_separator.offset => return Err(err),
+                Err(_) => break,
+            }
+        }
+        Ok((items, rest))
+    }
+}
+
+/// One or more `item`s separated by `separator`
+pub fn separated1<'a, T, U>(
+    expected: &'static str,
+    item: impl Parser<'a, T>,
+    separator: impl Parser<'a, U>,
+) -> impl Parser<'a, Vec<T>> {
+    let list = separated(item, separator);
+    move |input: Input<'a>| match list.parse(input)? {
+        (items, _) if items.is_empty() => input.error(expected),
+        result => Ok(result),
+    }
+}
+
+/// `item`s on consecutive lines, up to a blank line or the end of the input.
+/// Every line must be an `item`, so a bad line is reported where it goes wrong
+pub fn lines<'a, T>(item: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |input: Input<'a>| {
+        let mut items = Vec::new();
+        let mut rest = input;
+        loop {
+            let (next, after_item) = item.parse(rest)?;
+            items.push(next);
+            rest = after_item;
+
+            match line_ending().parse(rest) {
+                Ok((_, next_line))
+                    if !next_line.rest().trim_end().is_empty()
+                        && line_ending().parse(next_line).is_err() =>
+                {
+                    rest = next_line
+                }
+                _ => return Ok((items, rest)),
+            }
+        }
+    }
+}
+
+/// The end of a line followed by one or more blank lines
+fn blank_lines<'a>() -> impl Parser<'a, ()> {
+    |input: Input<'a>| {
+        let (_, rest) = line_ending().parse(input)?;
+        let (_, rest) = separated1("a blank line", line_ending(), literal("")).parse(rest)?;
+        Ok(((), rest))
+    }
+}
+
+/// `block`s separated by one or more blank lines, e.g. the pairs of packets in day 13
+pub fn blocks<'a, T>(block: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    separated(block, blank_lines())
+}
+
+/// `item` between an opening and closing bracket, e.g. `[item]`
+pub fn bracketed<'a, T>(
+    open: &'static str,
+    item: impl Parser<'a, T>,
+    close: &'static str,
+) -> impl Parser<'a, T> {
+    move |input: Input<'a>| {
+        let (_, rest) = literal(open).parse(input)?;
+        let (value, rest) = item.parse(rest)?;
+        let (_, rest) = literal(close).parse(rest)?;
+        Ok((value, rest))
+    }
+}
+
+/// A value that is either a leaf, or a bracketed list of nested values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Nested<T> {
+    Leaf(T),
+    List(Vec<Nested<T>>),
+}
+
+/// How deep `nested` lists can go. Each level takes a recursive call, so without a limit a long
+/// enough run of brackets would overflow the stack
+pub const MAX_NESTING: usize = 256;
+
+/// Arbitrarily nested bracketed lists of `leaf`s, e.g. `[1,[2,[]],3]`, up to `MAX_NESTING` deep
+pub fn nested<'a, T>(
+    open: &'static str,
+    separator: &'static str,
+    close: &'static str,
+    leaf: impl Parser<'a, T>,
+) -> impl Parser<'a, Nested<T>> {
+    move |input: Input<'a>| {
+        let too_deep = Cell::new(None);
+        parse_nested(input, open, separator, close, &leaf, 0, &too_deep).map_err(|err| {
+            // the limit fails a list with nothing in it, which reads as a missing `close`
+            match too_deep.get() {
+                Some(deepest) => ParseError::at(
+                    deepest,
+                    format!("lists nested at most {} deep", MAX_NESTING),
+                ),
+                None => err,
+            }
+        })
+    }
+}
+
+fn parse_nested<'a, T>(
+    input: Input<'a>,
+    open: &'static str,
+    separator: &'static str,
+    close: &'static str,
+    leaf: &impl Parser<'a, T>,
+    depth: usize,
+    too_deep: &Cell<Option<Input<'a>>>,
+) -> ParseResult<'a, Nested<T>> {
+    if depth == MAX_NESTING && input.rest().starts_with(open) {
+        too_deep.set(Some(input));
+        return input.error(format!("lists nested at most {} deep", MAX_NESTING));
+    }
+
+    let list = bracketed(
+        open,
+        separated(
+            |input| parse_nested(input, open, separator, close, leaf, depth + 1, too_deep),
+            literal(separator),
+        ),
+        close,
+    );
+    list.map(Nested::List)
+        .or(|input| {
+            leaf.parse(input)
+                .map(|(value, rest)| (Nested::Leaf(value), rest))
+        })
+        .parse(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        blocks, int, lines, literal, nested, parse_all, separated, signed, word, Nested, Parser,
+        MAX_NESTING,
+    };
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("42", Ok(42))]
+    #[case("-7", Ok(-7))]
+    #[case("-", Err("line 1, column 1: expected an integer, found '-'"))]
+    #[case("x", Err("line 1, column 1: expected an integer, found 'x'"))]
+    #[case(
+        "99999999999",
+        Err("line 1, column 1: expected a number that fits in i32, found '9'")
+    )]
+    #[case("4 2", Err("line 1, column 3: expected end of input, found '2'"))]
+    fn validate_signed(#[case] text: &str, #[case] expected: Result<i32, &str>) {
+        let result = parse_all(&signed::<i32>(), text).map_err(|err| err.to_string());
+        assert_eq!(expected.map_err(str::to_string), result);
+    }
+
+    #[test]
+    fn test_separated_and_literals() {
+        let parser = literal("move ")
+            .and(int::<usize>())
+            .skip(literal(" from "))
+            .and(separated(int::<usize>(), literal(",")))
+            .map(|((_, count), from)| (count, from));
+
+        assert_eq!(
+            Ok((3, vec![1, 2, 3])),
+            parse_all(&parser, "move 3 from 1,2,3")
+        );
+        assert_eq!(Ok((3, vec![])), parse_all(&parser, "move 3 from "));
+        assert_eq!(
+            "line 1, column 14: expected end of input, found ','",
+            parse_all(&parser, "move 3 from 1,")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_blocks_report_line_and_column() {
+        let pair = word().skip(literal(" ")).and(signed::<i32>());
+        let parser = blocks(lines(pair));
+
+        assert_eq!(
+            Ok(vec![
+                vec![("a", 1), ("b", -2)],
+                vec![("c", 3)],
+                vec![("d", 4)]
+            ]),
+            parse_all(&parser, "a 1\nb -2\n\nc 3\n\n\n\r\nd 4\n")
+        );
+
+        let err = parse_all(&parser, "a 1\nb -2\n\nc three\n").unwrap_err();
+        assert_eq!((4, 3), (err.line, err.column));
+        assert_eq!("an integer", err.expected);
+    }
+
+    #[test]
+    fn test_nested() {
+        let parser = nested("[", ",", "]", int::<u32>());
+        assert_eq!(
+            Ok(Nested::List(vec![
+                Nested::Leaf(1),
+                Nested::List(vec![Nested::Leaf(2), Nested::List(vec![])]),
+                Nested::Leaf(3)
+            ])),
+            parse_all(&parser, "[1,[2,[]],3]")
+        );
+
+        assert_eq!(
+            "line 1, column 6: expected ']', found ','",
+            parse_all(&parser, "[1,[2,]]").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "line 1, column 5: expected ']', found end of input",
+            parse_all(&parser, "[1,2").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_nested_depth_limit() {
+        let deepest = "[".repeat(MAX_NESTING) + &"]".repeat(MAX_NESTING);
+        // far too deep to recurse into, so it has to fail rather than overflow the stack
+        let too_deep = "[".repeat(200000) + &"]".repeat(200000);
+        let parser = nested("[", ",", "]", int::<u32>());
+
+        assert!(parse_all(&parser, &deepest).is_ok());
+
+        let err = parse_all(&parser, &too_deep).unwrap_err();
+        assert_eq!(MAX_NESTING + 1, err.column);
+        assert!(err.expected.contains("nested at most"), "{}", err);
+    }
+}
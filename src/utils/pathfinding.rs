@@ -0,0 +1,366 @@
+//! Shortest paths between points in a `Grid`.
+//!
+//! Searches step between neighbouring points inside the bounds of the points stored in the grid
+//! (not `Grid::bounds`, which always reaches the origin), so a `Grid` with a default
+//! value can stand in for the open space of a maze. Which steps are allowed, and what they cost, is
+//! up to the caller: `passable` and `cost` are given the point being left and the point being
+//! entered, each with its value.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+use super::{
+    grid::{Adjacency, Grid},
+    point::{Metric, Pt, Scalar},
+};
+
+/// The shortest distance to every point a search reached, and how it got there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths<const DIMS: usize, S: Scalar = isize> {
+    distances: HashMap<Pt<DIMS, S>, usize>,
+    /// the point before each point on its shortest path. Starts don't have one
+    previous: HashMap<Pt<DIMS, S>, Pt<DIMS, S>>,
+}
+
+#[allow(dead_code)]
+impl<const DIMS: usize, S: Scalar> Paths<DIMS, S> {
+    fn new() -> Self {
+        Self {
+            distances: HashMap::new(),
+            previous: HashMap::new(),
+        }
+    }
+
+    /// the shortest distance from any start to `pt`, or `None` if it can't be reached
+    pub fn distance(&self, pt: &Pt<DIMS, S>) -> Option<usize> {
+        self.distances.get(pt).copied()
+    }
+
+    /// the shortest distance to every point that was reached
+    pub fn distances(&self) -> &HashMap<Pt<DIMS, S>, usize> {
+        &self.distances
+    }
+
+    /// a shortest path to `pt`, from the start it's closest to through to `pt` itself
+    pub fn path_to(&self, pt: &Pt<DIMS, S>) -> Option<Vec<Pt<DIMS, S>>> {
+        if !self.distances.contains_key(pt) {
+            return None;
+        }
+
+        let mut path = vec![*pt];
+        while let Some(previous) = self.previous.get(path.last().unwrap()) {
+            path.push(*previous);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// the points one step from `pt` that are inside `bounds`, with their values. There's nowhere to
+/// go without any bounds, i.e. in an empty grid
+fn steps<'a, T: Copy, const DIMS: usize, S: Scalar>(
+    grid: &'a Grid<T, DIMS, S>,
+    pt: &'a Pt<DIMS, S>,
+    adjacency: Adjacency,
+    bounds: Option<([S; DIMS], [S; DIMS])>,
+) -> impl Iterator<Item = (Pt<DIMS, S>, T)> + 'a {
+    grid.adjacent_values(pt, adjacency)
+        .filter(move |(next, _)| {
+            bounds.map_or(false, |(mins, maxs)| {
+                (0..DIMS).all(|i| mins[i] <= next.0[i] && next.0[i] <= maxs[i])
+            })
+        })
+}
+
+/// Breadth-first search out from every one of `starts` at once, where every step costs 1
+#[allow(dead_code)]
+pub fn bfs<T: Copy, const DIMS: usize, S: Scalar>(
+    grid: &Grid<T, DIMS, S>,
+    adjacency: Adjacency,
+    starts: impl IntoIterator<Item = Pt<DIMS, S>>,
+    passable: impl Fn((Pt<DIMS, S>, T), (Pt<DIMS, S>, T)) -> bool,
+) -> Paths<DIMS, S> {
+    let bounds = grid.stored_bounds();
+    let mut paths = Paths::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if paths.distances.insert(start, 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(pt) = queue.pop_front() {
+        let here = (pt, grid.get_def(&pt));
+        let distance = paths.distances[&pt] + 1;
+
+        for next in steps(grid, &pt, adjacency, bounds) {
+            if paths.distances.contains_key(&next.0) || !passable(here, next) {
+                continue;
+            }
+            paths.distances.insert(next.0, distance);
+            paths.previous.insert(next.0, pt);
+            queue.push_back(next.0);
+        }
+    }
+
+    paths
+}
+
+/// Dijkstra's algorithm out from every one of `starts` at once, where each step costs `cost`
+#[allow(dead_code)]
+pub fn dijkstra<T: Copy, const DIMS: usize, S: Scalar>(
+    grid: &Grid<T, DIMS, S>,
+    adjacency: Adjacency,
+    starts: impl IntoIterator<Item = Pt<DIMS, S>>,
+    passable: impl Fn((Pt<DIMS, S>, T), (Pt<DIMS, S>, T)) -> bool,
+    cost: impl Fn((Pt<DIMS, S>, T), (Pt<DIMS, S>, T)) -> usize,
+) -> Paths<DIMS, S> {
+    search(grid, adjacency, starts, None, passable, cost)
+}
+
+/// A* search from the closest of `starts` to `goal`, returning the cost and the path there.
+/// Guided by the distance left to `goal` (Manhattan, or Chebyshev with diagonal steps), so every
+/// step needs to cost at least 1 for the path to be the shortest
+#[allow(dead_code)]
+pub fn a_star<T: Copy, const DIMS: usize, S: Scalar>(
+    grid: &Grid<T, DIMS, S>,
+    adjacency: Adjacency,
+    starts: impl IntoIterator<Item = Pt<DIMS, S>>,
+    goal: Pt<DIMS, S>,
+    passable: impl Fn((Pt<DIMS, S>, T), (Pt<DIMS, S>, T)) -> bool,
+    cost: impl Fn((Pt<DIMS, S>, T), (Pt<DIMS, S>, T)) -> usize,
+) -> Option<(usize, Vec<Pt<DIMS, S>>)> {
+    let paths = search(grid, adjacency, starts, Some(goal), passable, cost);
+    let path = paths.path_to(&goal)?;
+    Some((paths.distances[&goal], path))
+}
+
+/// Dijkstra's algorithm, or A* if there's a `goal` to stop at
+fn search<T: Copy, const DIMS: usize, S: Scalar>(
+    grid: &Grid<T, DIMS, S>,
+    adjacency: Adjacency,
+    starts: impl IntoIterator<Item = Pt<DIMS, S>>,
+    goal: Option<Pt<DIMS, S>>,
+    passable: impl Fn((Pt<DIMS, S>, T), (Pt<DIMS, S>, T)) -> bool,
+    cost: impl Fn((Pt<DIMS, S>, T), (Pt<DIMS, S>, T)) -> usize,
+) -> Paths<DIMS, S> {
+    let metric = match adjacency {
+        Adjacency::Cardinal => Metric::Manhattan,
+        Adjacency::Full => Metric::Chebyshev,
+    };
+    let heuristic = |pt: &Pt<DIMS, S>| {
+        goal.map_or(0, |goal| {
+            pt.dist(goal, metric).to_usize().unwrap_or(usize::MAX)
+        })
+    };
+
+    let bounds = grid.stored_bounds();
+    let mut paths = Paths::new();
+    let mut heap = BinaryHeap::new();
+
+    for start in starts {
+        paths.distances.insert(start, 0);
+        heap.push(Reverse((heuristic(&start), 0, start)));
+    }
+
+    while let Some(Reverse((_, distance, pt))) = heap.pop() {
+        if Some(pt) == goal {
+            break;
+        }
+        if paths.distances[&pt] < distance {
+            // already reached more cheaply
+            continue;
+        }
+
+        let here = (pt, grid.get_def(&pt));
+        for next in steps(grid, &pt, adjacency, bounds) {
+            if !passable(here, next) {
+                continue;
+            }
+            let next_distance = distance + cost(here, next);
+            if paths
+                .distances
+                .get(&next.0)
+                .map_or(true, |best| next_distance < *best)
+            {
+                paths.distances.insert(next.0, next_distance);
+                paths.previous.insert(next.0, pt);
+                heap.push(Reverse((
+                    next_distance.saturating_add(heuristic(&next.0)),
+                    next_distance,
+                    next.0,
+                )));
+            }
+        }
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::{a_star, bfs, dijkstra};
+    use crate::utils::{
+        grid::{Adjacency, Grid},
+        point::Pt,
+        property::{check, DEFAULT_CASES},
+    };
+
+    fn open(_: (Pt<2>, char), (_, to): (Pt<2>, char)) -> bool {
+        to != '#'
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = Grid::from_text(
+            "\
+..#.
+.##.
+....",
+            |c| c,
+        );
+
+        let paths = bfs(&grid, Adjacency::Cardinal, [Pt([0, 0])], open);
+        assert_eq!(Some(7), paths.distance(&Pt([3, 0])));
+        assert_eq!(None, paths.distance(&Pt([2, 0])));
+        assert_eq!(
+            Some(vec![Pt([0, 0]), Pt([0, 1]), Pt([0, 2]), Pt([1, 2])]),
+            paths.path_to(&Pt([1, 2]))
+        );
+        assert_eq!(9, paths.distances().len());
+
+        // diagonal steps cut the corners
+        let paths = bfs(&grid, Adjacency::Full, [Pt([0, 0])], open);
+        assert_eq!(Some(5), paths.distance(&Pt([3, 0])));
+
+        // from either end at once
+        let paths = bfs(&grid, Adjacency::Cardinal, [Pt([0, 0]), Pt([3, 0])], open);
+        assert_eq!(Some(3), paths.distance(&Pt([1, 2])));
+        assert_eq!(Some(3), paths.distance(&Pt([2, 2])));
+        assert_eq!(Pt([3, 0]), paths.path_to(&Pt([2, 2])).unwrap()[0]);
+    }
+
+    #[test]
+    fn test_stays_inside_stored_points() {
+        // away from the origin, with open space all around
+        let mut grid = Grid::<char, 2>::from(vec![
+            (Pt([5, 5]), '.'),
+            (Pt([6, 5]), '#'),
+            (Pt([5, 6]), '.'),
+            (Pt([7, 7]), '.'),
+        ]);
+        grid.set_default('.');
+
+        let paths = bfs(&grid, Adjacency::Cardinal, [Pt([5, 5])], open);
+        assert_eq!(8, paths.distances().len());
+        assert_eq!(Some(4), paths.distance(&Pt([7, 7])));
+        assert_eq!(None, paths.distance(&Pt([4, 5])));
+
+        let costs = dijkstra(&grid, Adjacency::Full, [Pt([5, 5])], open, |_, _| 1);
+        assert!(costs
+            .distances()
+            .keys()
+            .all(|pt| pt.0[0] >= 5 && pt.0[1] >= 5));
+    }
+
+    #[test]
+    fn test_dijkstra_and_a_star() {
+        let grid = Grid::from_text(
+            "\
+1163751
+1381373
+2136511
+3694931",
+            |c| c,
+        );
+        let cost = |_, (_, to): (Pt<2>, char)| to.to_digit(10).unwrap() as usize;
+        let end = Pt([6, 3]);
+
+        let paths = dijkstra(&grid, Adjacency::Cardinal, [Pt([0, 0])], |_, _| true, cost);
+        assert_eq!(Some(21), paths.distance(&end));
+
+        let (total, path) = a_star(
+            &grid,
+            Adjacency::Cardinal,
+            [Pt([0, 0])],
+            end,
+            |_, _| true,
+            cost,
+        )
+        .unwrap();
+        assert_eq!(21, total);
+        assert_eq!(Some(&end), path.last());
+        assert_eq!(
+            21,
+            path.iter()
+                .skip(1)
+                .map(|pt| grid.get_def(pt).to_digit(10).unwrap())
+                .sum::<u32>()
+        );
+
+        let walled = |_, (_, to): (Pt<2>, char)| to != '9';
+        assert_eq!(
+            None,
+            a_star(
+                &grid,
+                Adjacency::Cardinal,
+                [Pt([0, 0])],
+                Pt([2, 3]),
+                walled,
+                cost
+            )
+        );
+    }
+
+    #[test]
+    fn a_star_matches_dijkstra() {
+        const WIDTH: usize = 6;
+
+        check(
+            DEFAULT_CASES,
+            46,
+            |rng| (rng.vec(1, 36, |rng| rng.range(0, 5)), rng.bool()),
+            |(costs, diagonal)| {
+                // 0 is a wall
+                let grid = Grid::<i64, 2>::from(
+                    costs
+                        .iter()
+                        .enumerate()
+                        .map(|(i, cost)| (Pt([(i % WIDTH) as isize, (i / WIDTH) as isize]), *cost))
+                        .collect_vec(),
+                );
+                let adjacency = if *diagonal {
+                    Adjacency::Full
+                } else {
+                    Adjacency::Cardinal
+                };
+                let passable = |_, (_, to): (Pt<2>, i64)| to != 0;
+                let cost = |_, (_, to): (Pt<2>, i64)| to as usize;
+                let start = Pt([0, 0]);
+                let goal = Pt([
+                    ((costs.len() - 1) % WIDTH) as isize,
+                    ((costs.len() - 1) / WIDTH) as isize,
+                ]);
+
+                let expected = dijkstra(&grid, adjacency, [start], passable, cost).distance(&goal);
+                let found = a_star(&grid, adjacency, [start], goal, passable, cost);
+
+                if found.as_ref().map(|(total, _)| *total) != expected {
+                    return Err(format!("A* found {:?}, expected {:?}", found, expected));
+                }
+                if let Some((total, path)) = found {
+                    let path_cost: i64 = path.iter().skip(1).map(|pt| grid.get_def(pt)).sum();
+                    if path_cost as usize != total || path[0] != start {
+                        return Err(format!("path {:?} doesn't cost {}", path, total));
+                    }
+                }
+                Ok(())
+            },
+        );
+    }
+}
@@ -1,45 +1,104 @@
 use std::{
+    any::type_name,
+    array,
     collections::HashSet,
-    ops::{Add, Mul, Neg},
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Rem, Sub, SubAssign},
+    str::FromStr,
 };
 
+use anyhow::{anyhow, Error, Result};
 use itertools::Itertools;
+use num::{traits::Euclid, NumCast, PrimInt, Signed};
+use regex::Regex;
 
-#[derive(Hash, PartialEq, Eq, PartialOrd, Debug, Clone, Copy)]
-pub struct Pt<const DIMS: usize>(pub [isize; DIMS]);
+use super::{
+    input_error::InputError,
+    parser::{literal, parse_all, separated1, signed, take_while, Input, ParseError, Parser},
+};
+
+/// A signed integer type that can be used for the coordinates of a `Pt`,
+/// e.g. `i16` to save memory, or `i128` for huge spaces
+pub trait Scalar:
+    PrimInt + Signed + Euclid + Hash + Debug + Display + Default + AddAssign + SubAssign + MulAssign
+{
+}
+
+impl<T> Scalar for T where
+    T: PrimInt
+        + Signed
+        + Euclid
+        + Hash
+        + Debug
+        + Display
+        + Default
+        + AddAssign
+        + SubAssign
+        + MulAssign
+{
+}
+
+/// A point, or an offset between points, in `DIMS` dimensions, with coordinates of type `T`.
+/// Ordered lexicographically by its coordinates
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub struct Pt<const DIMS: usize, T = isize>(pub [T; DIMS]);
 
-impl<const DIMS: usize> Default for Pt<DIMS> {
+impl<const DIMS: usize, T: Scalar> Default for Pt<DIMS, T> {
     fn default() -> Self {
-        Self([0; DIMS])
+        Self([T::zero(); DIMS])
     }
 }
 
-impl<const DIMS: usize> Neg for Pt<DIMS> {
-    type Output = Pt<DIMS>;
+impl<const DIMS: usize, T: Scalar> Neg for Pt<DIMS, T> {
+    type Output = Pt<DIMS, T>;
 
     fn neg(mut self) -> Self::Output {
         for v in &mut self.0 {
-            *v *= -1
+            *v = -*v
         }
         self
     }
 }
 
-impl<const DIMS: usize> Add for Pt<DIMS> {
-    type Output = Pt<DIMS>;
+impl<const DIMS: usize, T: Scalar> Add for Pt<DIMS, T> {
+    type Output = Pt<DIMS, T>;
 
     fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<const DIMS: usize, T: Scalar> AddAssign for Pt<DIMS, T> {
+    fn add_assign(&mut self, rhs: Self) {
         for (i, a) in &mut self.0.iter_mut().enumerate() {
             *a += rhs.0[i]
         }
+    }
+}
+
+impl<const DIMS: usize, T: Scalar> Sub for Pt<DIMS, T> {
+    type Output = Pt<DIMS, T>;
+
+    fn sub(mut self, rhs: Self) -> Self::Output {
+        self -= rhs;
         self
     }
 }
 
-impl<const DIMS: usize> Mul<isize> for Pt<DIMS> {
-    type Output = Pt<DIMS>;
+impl<const DIMS: usize, T: Scalar> SubAssign for Pt<DIMS, T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (i, a) in &mut self.0.iter_mut().enumerate() {
+            *a -= rhs.0[i]
+        }
+    }
+}
+
+impl<const DIMS: usize, T: Scalar> Mul<T> for Pt<DIMS, T> {
+    type Output = Pt<DIMS, T>;
 
-    fn mul(mut self, rhs: isize) -> Self::Output {
+    fn mul(mut self, rhs: T) -> Self::Output {
         for v in &mut self.0 {
             *v *= rhs;
         }
@@ -47,41 +106,407 @@ impl<const DIMS: usize> Mul<isize> for Pt<DIMS> {
     }
 }
 
+/// component-wise
+impl<const DIMS: usize, T: Scalar> Mul for Pt<DIMS, T> {
+    type Output = Pt<DIMS, T>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a * b)
+    }
+}
+
+/// component-wise, rounding towards zero like integer division
+impl<const DIMS: usize, T: Scalar> Div for Pt<DIMS, T> {
+    type Output = Pt<DIMS, T>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a / b)
+    }
+}
+
+/// component-wise, with the sign of the left hand side like `%`. Use `rem_euclid` to wrap around a grid
+impl<const DIMS: usize, T: Scalar> Rem for Pt<DIMS, T> {
+    type Output = Pt<DIMS, T>;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        self.zip_with(rhs, |a, b| a % b)
+    }
+}
+
+/// Exactly `DIMS` coordinates
+impl<const DIMS: usize, T: Scalar> TryFrom<Vec<T>> for Pt<DIMS, T> {
+    type Error = Error;
+
+    fn try_from(coords: Vec<T>) -> Result<Self> {
+        let found = coords.len();
+        let coords = coords
+            .try_into()
+            .map_err(|_| anyhow!("Expected {} coordinates, found {}", DIMS, found))?;
+        Ok(Pt(coords))
+    }
+}
+
+/// Anything the `pt` parser accepts, e.g. `1,2,3`, `(1, 2)`, `x=1, y=-2` or `<x=1, y=2, z=3>`
+impl<const DIMS: usize, T: Scalar + FromStr> FromStr for Pt<DIMS, T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        parse_all(&pt(), s.trim())
+    }
+}
+
+/// Ways of measuring the distance between two points
 #[allow(dead_code)]
-impl<const DIMS: usize> Pt<DIMS> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// the sum of the distances along each axis
+    Manhattan,
+    /// the largest distance along any axis, i.e. moves when diagonal moves are allowed
+    Chebyshev,
+    /// the square of the straight line distance, which stays an integer
+    EuclideanSquared,
+}
+
+#[allow(dead_code)]
+impl<const DIMS: usize, T: Scalar> Pt<DIMS, T> {
     /// get all the offsets required to get every neighbour to a position
-    pub fn neighbour_offsets() -> HashSet<Pt<DIMS>> {
-        vec![[-1, 0, 1]; DIMS]
+    pub fn neighbour_offsets() -> HashSet<Pt<DIMS, T>> {
+        vec![[-T::one(), T::zero(), T::one()]; DIMS]
             .into_iter()
             .multi_cartesian_product()
             .map(|vec| vec.try_into().unwrap())
-            .filter(|arr| arr != &[0; DIMS])
+            .filter(|arr| arr != &[T::zero(); DIMS])
             .map(Pt)
             .collect()
     }
 
     /// get all the offsets required to get every cardinal (non-diagonal) neighbour to a position
-    pub fn card_offsets() -> HashSet<Pt<DIMS>> {
-        let mut pts = vec![[0; DIMS]; DIMS * 2];
+    pub fn card_offsets() -> HashSet<Pt<DIMS, T>> {
+        let mut pts = vec![[T::zero(); DIMS]; DIMS * 2];
 
         for i in 0..DIMS {
-            pts[i][i] = 1;
-            pts[i + DIMS][i] = -1;
+            pts[i][i] = T::one();
+            pts[i + DIMS][i] = -T::one();
         }
 
         pts.into_iter().map(Pt).collect()
     }
 
-    pub fn mag(&self) -> isize {
-        self.0.iter().map(|v| v.abs()).sum()
+    /// the Manhattan distance from the origin
+    pub fn mag(&self) -> T {
+        self.0.iter().fold(T::zero(), |total, v| total + v.abs())
+    }
+
+    /// the Chebyshev distance from the origin
+    pub fn chebyshev(&self) -> T {
+        self.0.iter().fold(T::zero(), |max, v| max.max(v.abs()))
+    }
+
+    /// the square of the Euclidean distance from the origin
+    pub fn euclidean_sq(&self) -> T {
+        self.dot(*self)
+    }
+
+    /// the distance to `other`, measured with `metric`
+    pub fn dist(&self, other: Pt<DIMS, T>, metric: Metric) -> T {
+        let offset = other - *self;
+        match metric {
+            Metric::Manhattan => offset.mag(),
+            Metric::Chebyshev => offset.chebyshev(),
+            Metric::EuclideanSquared => offset.euclidean_sq(),
+        }
+    }
+
+    pub fn dot(&self, other: Pt<DIMS, T>) -> T {
+        self.0
+            .iter()
+            .zip(other.0)
+            .fold(T::zero(), |total, (a, b)| total + *a * b)
+    }
+
+    /// each coordinate replaced by -1, 0 or 1 depending on its sign.
+    /// For an offset between two points, this is one step from the first towards the second
+    pub fn signum(&self) -> Pt<DIMS, T> {
+        Pt(self.0.map(|v| v.signum()))
+    }
+
+    /// the component-wise minimum, unlike `Ord::min` which compares whole points
+    pub fn component_min(self, other: Pt<DIMS, T>) -> Pt<DIMS, T> {
+        self.zip_with(other, T::min)
+    }
+
+    /// the component-wise maximum, unlike `Ord::max` which compares whole points
+    pub fn component_max(self, other: Pt<DIMS, T>) -> Pt<DIMS, T> {
+        self.zip_with(other, T::max)
+    }
+
+    /// the component-wise euclidean remainder, which is never negative
+    pub fn rem_euclid(&self, other: Pt<DIMS, T>) -> Pt<DIMS, T> {
+        self.zip_with(other, |a, b| a.rem_euclid(&b))
+    }
+
+    /// the same point with coordinates of another type, e.g. `pt.cast::<i16>()`,
+    /// or an error if a coordinate doesn't fit
+    pub fn cast<U: Scalar>(&self) -> Result<Pt<DIMS, U>> {
+        let mut output = Pt::<DIMS, U>::default();
+        for (to, from) in output.0.iter_mut().zip(self.0) {
+            *to = <U as NumCast>::from(from).ok_or_else(|| {
+                anyhow!(
+                    "Coordinate {} of {:?} doesn't fit in {}",
+                    from,
+                    self.0,
+                    type_name::<U>()
+                )
+            })?;
+        }
+        Ok(output)
+    }
+
+    fn zip_with(&self, other: Pt<DIMS, T>, f: impl Fn(T, T) -> T) -> Pt<DIMS, T> {
+        let mut output = *self;
+        for (a, b) in output.0.iter_mut().zip(other.0) {
+            *a = f(*a, b);
+        }
+        output
+    }
+}
+
+/// A straight line between two points, including both ends
+#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Segment<const DIMS: usize> {
+    pub start: Pt<DIMS>,
+    pub end: Pt<DIMS>,
+}
+
+#[allow(dead_code)]
+impl<const DIMS: usize> Segment<DIMS> {
+    pub fn new(start: Pt<DIMS>, end: Pt<DIMS>) -> Self {
+        Self { start, end }
+    }
+
+    /// the segments joining each point to the next. A lone point is a segment of length 0,
+    /// so it still gets drawn
+    pub fn polyline(points: &[Pt<DIMS>]) -> Vec<Self> {
+        match points {
+            [only] => vec![Self::new(*only, *only)],
+            _ => points
+                .iter()
+                .tuple_windows()
+                .map(|(&start, &end)| Self::new(start, end))
+                .collect(),
+        }
+    }
+
+    /// whether the segment runs along a single axis (or is a single point)
+    pub fn is_axis_aligned(&self) -> bool {
+        (self.end - self.start)
+            .0
+            .iter()
+            .filter(|v| **v != 0)
+            .count()
+            <= 1
+    }
+
+    /// whether the segment moves the same distance along every axis it moves along,
+    /// e.g. at 45° in 2D. Axis-aligned segments count too
+    pub fn is_diagonal(&self) -> bool {
+        let offset = self.end - self.start;
+        offset
+            .0
+            .iter()
+            .all(|v| *v == 0 || v.abs() == offset.chebyshev())
+    }
+
+    /// every lattice point from `start` to `end`, in order, one step apart in Chebyshev distance.
+    /// Axis-aligned and diagonal segments land exactly on the points between the ends, and other
+    /// segments pick the nearest point at each step like Bresenham's algorithm,
+    /// rounding halfway points away from `start`
+    pub fn points(&self) -> impl Iterator<Item = Pt<DIMS>> {
+        let Segment { start, end } = *self;
+        let offset = end - start;
+        let steps = offset.chebyshev();
+
+        (0..=steps).map(move |step| {
+            if steps == 0 {
+                return start;
+            }
+            Pt(array::from_fn(|i| {
+                // offset * step / steps, rounded to the nearest integer
+                let scaled = offset.0[i] * step;
+                start.0[i] + (2 * scaled + scaled.signum() * steps) / (2 * steps)
+            }))
+        })
+    }
+
+    /// the number of points `points` gives
+    pub fn len(&self) -> usize {
+        (self.end - self.start).chebyshev() as usize + 1
+    }
+
+    /// never true, as a segment always includes its ends
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+#[allow(dead_code)]
+impl Segment<2> {
+    /// whether the line through `start` and `end` turns clockwise, anticlockwise,
+    /// or doesn't turn (0) to get to `pt`. y points down, so clockwise is as it looks when printed
+    fn turn_to(&self, pt: Pt<2>) -> isize {
+        let [ax, ay] = (self.end - self.start).0;
+        let [bx, by] = (pt - self.start).0;
+        (ax * by - ay * bx).signum()
+    }
+
+    /// whether `pt` lies on the segment, not just on the same line
+    pub fn contains(&self, pt: Pt<2>) -> bool {
+        let (min, max) = (
+            self.start.component_min(self.end),
+            self.start.component_max(self.end),
+        );
+        self.turn_to(pt) == 0 && pt.component_max(min).component_min(max) == pt
+    }
+
+    /// whether the segments cross or touch anywhere, as lines rather than lattice points
+    pub fn intersects(&self, other: &Segment<2>) -> bool {
+        let (d1, d2) = (self.turn_to(other.start), self.turn_to(other.end));
+        let (d3, d4) = (other.turn_to(self.start), other.turn_to(self.end));
+
+        if d1 * d2 < 0 && d3 * d4 < 0 {
+            return true;
+        }
+
+        self.contains(other.start)
+            || self.contains(other.end)
+            || other.contains(self.start)
+            || other.contains(self.end)
+    }
+}
+
+/// The names coordinates can be labelled with, for each axis
+pub(crate) const AXES: [&str; 4] = ["x", "y", "z", "w"];
+
+/// A point written as its coordinates separated by commas, e.g. `498,-4` or `(1, 2)`.
+/// It can be wrapped in `()`, `<>` or `[]`, and coordinates can be labelled with their axis,
+/// e.g. `x=1, y=-2` or `<x=1, y=2, z=3>`. Labelled coordinates can come in any order
+pub fn pt<'a, const DIMS: usize, T: Scalar + FromStr>() -> impl Parser<'a, Pt<DIMS, T>> {
+    let spaces = || take_while(|c| c == ' ');
+    let open = literal("(")
+        .or(literal("<"))
+        .or(literal("["))
+        .or(literal(""));
+    let label = take_while(|c| c.is_ascii_alphabetic());
+
+    move |input: Input<'a>| {
+        let (opened, mut rest) = open.parse(input)?;
+        let mut coords = [None; DIMS];
+        // only the first 4 axes have names, so any more can only be given in order
+        let axes = &AXES[..DIMS.min(AXES.len())];
+
+        for i in 0..DIMS {
+            if i > 0 {
+                rest = match literal(",").parse(rest) {
+                    Ok((_, after_comma)) => after_comma,
+                    Err(_) => return rest.error(format!("{} coordinates", DIMS)),
+                }
+            }
+            let (_, item) = spaces().parse(rest)?;
+
+            // a label is only a label if it's followed by `=`, otherwise it's a bad number
+            let (axis, after_label) = match label.parse(item)? {
+                (name, after_name) if !name.is_empty() && after_name.rest().starts_with('=') => {
+                    match axes.iter().position(|axis| *axis == name) {
+                        Some(axis) => (axis, literal("=").parse(after_name)?.1),
+                        None => return item.error(format!("one of the axes {}", axes.join(", "))),
+                    }
+                }
+                _ => (i, item),
+            };
+            if coords[axis].is_some() {
+                return item.error(format!(
+                    "each of the axes once, but {} is repeated",
+                    AXES[axis]
+                ));
+            }
+
+            let (value, after_value) = signed::<T>().parse(after_label)?;
+            coords[axis] = Some(value);
+            rest = after_value;
+        }
+
+        // only skip trailing spaces if they're inside brackets, so a separator can start with one
+        let (_, after_spaces) = spaces().parse(rest)?;
+        if after_spaces.rest().starts_with(',') {
+            return after_spaces.error(format!("only {} coordinates", DIMS));
+        }
+        let rest = match opened {
+            "(" => literal(")").parse(after_spaces)?.1,
+            "<" => literal(">").parse(after_spaces)?.1,
+            "[" => literal("]").parse(after_spaces)?.1,
+            _ => rest,
+        };
+
+        // every axis has been filled in, as there are DIMS coordinates and none are repeated
+        Ok((Pt(coords.map(|coord| coord.unwrap_or_default())), rest))
+    }
+}
+
+/// Points joined by ` -> `, e.g. `498,4 -> 498,6 -> 496,6`, as the segments between them
+pub fn polyline<'a, const DIMS: usize>() -> impl Parser<'a, Vec<Segment<DIMS>>> {
+    separated1("a point", pt::<DIMS, isize>(), literal(" -> "))
+        .map(|points| Segment::polyline(&points))
+}
+
+thread_local! {
+    /// the integers in a line for `extract_points`, compiled once rather than for every line
+    static NUMBERS: Regex = Regex::new(r"-?\d+").unwrap();
+}
+
+/// Every point in a line of text, made from its integers `DIMS` at a time and ignoring anything
+/// else, e.g. the two points in `Sensor at x=2, y=18: closest beacon is at x=-2, y=15`
+pub fn extract_points<const DIMS: usize, T: Scalar + FromStr>(
+    line: &str,
+) -> Result<Vec<Pt<DIMS, T>>, InputError> {
+    let numbers = NUMBERS.with(|numbers| numbers.find_iter(line).collect_vec());
+
+    let mut coords = Vec::new();
+    for number in numbers {
+        let value = number.as_str().parse::<T>().map_err(|_| {
+            InputError::at_offset(
+                line,
+                number.start(),
+                number.as_str().len(),
+                format!("{} doesn't fit in a coordinate", number.as_str()),
+            )
+        })?;
+        coords.push(value);
+    }
+
+    if coords.len() % DIMS != 0 {
+        return Err(InputError::at_line(
+            line,
+            1,
+            format!(
+                "Found {} numbers, which can't be split into points of {}",
+                coords.len(),
+                DIMS
+            ),
+        ));
     }
+
+    Ok(coords
+        .chunks(DIMS)
+        .map(|chunk| Pt(chunk.try_into().unwrap()))
+        .collect())
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
-    use super::Pt;
+    use super::{extract_points, polyline, Metric, Pt, Scalar, Segment};
+    use crate::utils::parser::parse_all;
     use rstest::rstest;
 
     #[test]
@@ -168,10 +593,232 @@ mod tests {
         assert_eq!(expected, a + b)
     }
 
+    #[rstest]
+    #[case(Pt([1,2,3,4]), Pt([1,2,0,0]), Pt([0,0,3,4]))]
+    #[case(Pt([-102,34,0,-3]), Pt([100,14,-10000,999]), Pt([-202,20,10000,-1002]))]
+    fn validate_sub(#[case] a: Pt<4>, #[case] b: Pt<4>, #[case] expected: Pt<4>) {
+        assert_eq!(expected, a - b);
+
+        let mut assigned = a;
+        assigned -= b;
+        assert_eq!(expected, assigned);
+        assigned += b;
+        assert_eq!(a, assigned);
+    }
+
     #[rstest]
     #[case(Pt([100,200,300]), Pt([1,2,3]), 100)]
     #[case(Pt([-100,-200,-300]), Pt([1,2,3]), -100)]
     fn validate_mul(#[case] expected: Pt<3>, #[case] a: Pt<3>, #[case] b: isize) {
         assert_eq!(expected, a * b)
     }
+
+    #[test]
+    fn test_component_wise() {
+        let a = Pt([7, -7, 3]);
+        let b = Pt([2, 2, -4]);
+
+        assert_eq!(Pt([14, -14, -12]), a * b);
+        assert_eq!(Pt([3, -3, 0]), a / b);
+        assert_eq!(Pt([1, -1, 3]), a % b);
+        assert_eq!(Pt([1, 1, 3]), a.rem_euclid(b));
+        assert_eq!(Pt([2, -7, -4]), a.component_min(b));
+        assert_eq!(Pt([7, 2, 3]), a.component_max(b));
+        assert_eq!(Pt([1, -1, 1]), a.signum());
+        assert_eq!(Pt([0, 0]), Pt::<2>::default().signum());
+        assert_eq!(-12, a.dot(b));
+    }
+
+    #[rstest]
+    #[case(Metric::Manhattan, 7)]
+    #[case(Metric::Chebyshev, 4)]
+    #[case(Metric::EuclideanSquared, 25)]
+    fn validate_dist(#[case] metric: Metric, #[case] expected: isize) {
+        assert_eq!(expected, Pt([1, 5]).dist(Pt([4, 1]), metric));
+        assert_eq!(expected, Pt([4, 1]).dist(Pt([1, 5]), metric));
+    }
+
+    #[test]
+    fn test_other_scalars() {
+        fn check<T: Scalar>() {
+            let a = Pt::<3, T>([7, -7, 3].map(|v| T::from(v).unwrap()));
+            let b = Pt::<3, T>([2, 2, -4].map(|v| T::from(v).unwrap()));
+
+            assert_eq!(Ok(Pt([9, -5, -1])), (a + b).cast::<isize>().map_err(|_| ()));
+            assert_eq!(
+                Ok(Pt([1, 1, 3])),
+                a.rem_euclid(b).cast::<isize>().map_err(|_| ())
+            );
+            assert_eq!(T::from(17).unwrap(), a.mag());
+            assert_eq!(T::from(21).unwrap(), a.dist(b, Metric::Manhattan));
+            assert_eq!(26, Pt::<3, T>::neighbour_offsets().len());
+            assert_eq!(6, Pt::<3, T>::card_offsets().len());
+        }
+
+        check::<i16>();
+        check::<i32>();
+        check::<i64>();
+        check::<i128>();
+    }
+
+    #[test]
+    fn test_cast_is_checked() {
+        let big = Pt::<2, i128>([1 << 40, -3]);
+        assert_eq!(Pt([1 << 40, -3]), big.cast::<i64>().unwrap());
+        assert!(big.cast::<i32>().is_err());
+        assert_eq!(Pt([-3, 3]), Pt::<2, i128>([-3, 3]).cast::<i16>().unwrap());
+        assert!(Pt([40_000, 0]).cast::<i16>().is_err());
+    }
+
+    #[rstest]
+    #[case("1,2,3", [1, 2, 3])]
+    #[case("(1, -2, 3)", [1, -2, 3])]
+    #[case("x=1, y=-2, z=3", [1, -2, 3])]
+    #[case("<x=1, y=2, z=3>", [1, 2, 3])]
+    #[case("[z=3,x=1,y=2]", [1, 2, 3])]
+    #[case("  -4,0,4", [-4, 0, 4])]
+    #[case("-4,0,4\n", [-4, 0, 4])]
+    #[case(" (1, 2, 3)  ", [1, 2, 3])]
+    fn validate_from_str(#[case] input: &str, #[case] expected: [i32; 3]) {
+        assert_eq!(Pt(expected), input.parse::<Pt<3, i32>>().unwrap());
+    }
+
+    #[rstest]
+    #[case("1,2", "line 1, column 4: expected 3 coordinates, found end of input")]
+    #[case("1,2,3,4", "line 1, column 6: expected only 3 coordinates, found ','")]
+    #[case("(1,2,3", "line 1, column 7: expected ')', found end of input")]
+    #[case(
+        "x=1, x=2, z=3",
+        "line 1, column 6: expected each of the axes once, but x is repeated, found 'x'"
+    )]
+    #[case(
+        "x=1, y=2, w=3",
+        "line 1, column 11: expected one of the axes x, y, z, found 'w'"
+    )]
+    #[case("1,a,3", "line 1, column 3: expected an integer, found 'a'")]
+    #[case(
+        "1,2,99999",
+        "line 1, column 5: expected a number that fits in i16, found '9'"
+    )]
+    fn validate_from_str_errors(#[case] input: &str, #[case] expected: &str) {
+        let err = input.parse::<Pt<3, i16>>().unwrap_err();
+        assert_eq!(expected, err.to_string());
+    }
+
+    #[test]
+    fn test_from_str_beyond_named_axes() {
+        assert_eq!(
+            Pt([1, 2, 3, 4, 5]),
+            "w=4, y=2, x=1, z=3, 5".parse::<Pt<5>>().unwrap()
+        );
+        assert!("x=1".parse::<Pt<5>>().is_err());
+        assert_eq!(
+            "line 1, column 1: expected one of the axes x, y, z, w, found 'v'",
+            "v=1,2,3,4,5".parse::<Pt<5>>().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_try_from_vec() {
+        assert_eq!(Pt([1, 2]), Pt::<2>::try_from(vec![1, 2]).unwrap());
+        assert_eq!(
+            "Expected 2 coordinates, found 3",
+            Pt::<2>::try_from(vec![1, 2, 3]).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_extract_points() {
+        let line = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15";
+        assert_eq!(
+            vec![Pt([2, 18]), Pt([-2, 15])],
+            extract_points::<2, isize>(line).unwrap()
+        );
+        assert!(extract_points::<3, isize>(line).is_err());
+        assert!(extract_points::<2, i16>("x=99999, y=0").is_err());
+        assert_eq!(
+            Vec::<Pt<2>>::new(),
+            extract_points::<2, isize>("no numbers").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ord_is_lexicographic() {
+        let mut points = vec![Pt([1, -1]), Pt([0, 5]), Pt([1, -3]), Pt([-2, 8])];
+        points.sort();
+        assert_eq!(
+            vec![Pt([-2, 8]), Pt([0, 5]), Pt([1, -3]), Pt([1, -1])],
+            points
+        );
+        assert_eq!(Some(&Pt([1, -1])), points.iter().max());
+    }
+
+    #[rstest]
+    #[case::axis_aligned(Pt([2, 3]), Pt([2, 0]), vec![[2, 3], [2, 2], [2, 1], [2, 0]])]
+    #[case::diagonal(Pt([0, 0]), Pt([-2, 2]), vec![[0, 0], [-1, 1], [-2, 2]])]
+    #[case::bresenham(Pt([0, 0]), Pt([4, 2]), vec![[0, 0], [1, 1], [2, 1], [3, 2], [4, 2]])]
+    #[case::single_point(Pt([5, 5]), Pt([5, 5]), vec![[5, 5]])]
+    fn validate_segment_points(
+        #[case] start: Pt<2>,
+        #[case] end: Pt<2>,
+        #[case] expected: Vec<[isize; 2]>,
+    ) {
+        let segment = Segment::new(start, end);
+        let points = segment.points().collect::<Vec<_>>();
+        assert_eq!(expected.into_iter().map(Pt).collect::<Vec<_>>(), points);
+        assert_eq!(points.len(), segment.len());
+    }
+
+    #[test]
+    fn test_segment_kinds() {
+        let diagonal = Segment::new(Pt([1, 1, 1]), Pt([4, -2, 4]));
+        assert!(diagonal.is_diagonal());
+        assert!(!diagonal.is_axis_aligned());
+        assert_eq!(Pt([2, 0, 2]), diagonal.points().nth(1).unwrap());
+
+        let along_z = Segment::new(Pt([1, 1, 1]), Pt([1, 1, -6]));
+        assert!(along_z.is_axis_aligned());
+        assert!(along_z.is_diagonal());
+
+        assert!(!Segment::new(Pt([0, 0]), Pt([4, 2])).is_diagonal());
+    }
+
+    #[rstest]
+    #[case::crossing([[0, 0], [4, 4]], [[0, 4], [4, 0]], true)]
+    #[case::t_junction([[0, 0], [4, 0]], [[2, 0], [2, 5]], true)]
+    #[case::touching_ends([[0, 0], [2, 2]], [[2, 2], [5, 0]], true)]
+    #[case::overlapping([[0, 0], [4, 0]], [[3, 0], [9, 0]], true)]
+    #[case::collinear_apart([[0, 0], [2, 0]], [[3, 0], [9, 0]], false)]
+    #[case::parallel([[0, 0], [4, 0]], [[0, 1], [4, 1]], false)]
+    #[case::would_cross_if_longer([[0, 0], [2, 2]], [[0, 4], [1, 3]], false)]
+    fn validate_intersects(
+        #[case] a: [[isize; 2]; 2],
+        #[case] b: [[isize; 2]; 2],
+        #[case] expected: bool,
+    ) {
+        let a = Segment::new(Pt(a[0]), Pt(a[1]));
+        let b = Segment::new(Pt(b[0]), Pt(b[1]));
+        assert_eq!(expected, a.intersects(&b));
+        assert_eq!(expected, b.intersects(&a));
+    }
+
+    #[test]
+    fn test_parse_polyline() {
+        let segments = parse_all(&polyline::<2>(), "498,4 -> 498,6 -> 496,6").unwrap();
+        assert_eq!(
+            vec![
+                Segment::new(Pt([498, 4]), Pt([498, 6])),
+                Segment::new(Pt([498, 6]), Pt([496, 6]))
+            ],
+            segments
+        );
+
+        let lone_point = parse_all(&polyline::<3>(), "1,-2,3").unwrap();
+        assert_eq!(
+            vec![Segment::new(Pt([1, -2, 3]), Pt([1, -2, 3]))],
+            lone_point
+        );
+
+        assert!(parse_all(&polyline::<3>(), "1,2 -> 3,4").is_err());
+    }
 }
@@ -0,0 +1,200 @@
+use std::{
+    cell::Cell,
+    error::Error,
+    fmt::Display,
+    io::{stderr, Write},
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::Result;
+
+/// Set by the Ctrl-C handler. Solvers notice it through `Progress::check`
+pub static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+const BAR_WIDTH: usize = 40;
+
+/// Install a Ctrl-C handler that asks long-running solvers to stop.
+/// A second Ctrl-C kills the process, for solvers that never check for cancellation
+pub fn cancel_on_ctrlc() -> Result<()> {
+    ctrlc::set_handler(|| {
+        if CANCELLED.swap(true, Ordering::SeqCst) {
+            process::exit(130);
+        }
+        eprintln!("\nCancelling... press Ctrl-C again to stop immediately");
+    })?;
+    Ok(())
+}
+
+/// The error returned by a solver that noticed it was cancelled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cancelled {
+    /// what the solver was doing
+    pub label: String,
+    /// how far the solver got, as described by the solver
+    pub partial: String,
+}
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cancelled during {}: {}", self.label, self.partial)
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Passed into solvers so that long-running parts can report how far they've got,
+/// and notice when they've been cancelled
+pub struct Progress<'a> {
+    label: String,
+    cancelled: Option<&'a AtomicBool>,
+    show_bar: bool,
+    /// the last percentage drawn, so we only redraw when it changes
+    last_percent: Cell<Option<usize>>,
+}
+
+impl Progress<'static> {
+    /// A progress context that draws nothing and is never cancelled
+    pub fn none() -> Self {
+        Self {
+            label: String::new(),
+            cancelled: None,
+            show_bar: false,
+            last_percent: Cell::new(None),
+        }
+    }
+}
+
+impl<'a> Progress<'a> {
+    /// A progress context that draws a progress bar on stderr, and is cancelled when `cancelled` is set
+    pub fn new(label: &str, cancelled: &'a AtomicBool) -> Self {
+        Self {
+            label: label.to_string(),
+            cancelled: Some(cancelled),
+            show_bar: true,
+            last_percent: Cell::new(None),
+        }
+    }
+
+    /// A progress context that is cancelled when `cancelled` is set, but draws nothing
+    pub fn silent(label: &str, cancelled: &'a AtomicBool) -> Self {
+        Self {
+            label: label.to_string(),
+            cancelled: Some(cancelled),
+            show_bar: false,
+            last_percent: Cell::new(None),
+        }
+    }
+
+    /// Report how much of the work is done, as a fraction between 0 and 1
+    pub fn report(&self, fraction: f64) {
+        if !self.show_bar {
+            return;
+        }
+
+        let fraction = fraction.clamp(0.0, 1.0);
+        let percent = (fraction * 100.0) as usize;
+        if self.last_percent.get() == Some(percent) {
+            return;
+        }
+        self.last_percent.set(Some(percent));
+
+        let filled = (fraction * BAR_WIDTH as f64) as usize;
+        let mut err = stderr().lock();
+        // progress is best-effort, so failing to draw it shouldn't fail the solver
+        let _ = write!(
+            err,
+            "\r{} [{}{}] {:>3}%",
+            self.label,
+            "#".repeat(filled),
+            ".".repeat(BAR_WIDTH - filled),
+            percent
+        );
+        let _ = err.flush();
+    }
+
+    /// Whether the solver has been asked to stop
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+            .map(|cancelled| cancelled.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    /// Return a `Cancelled` error if the solver has been asked to stop.
+    /// `partial` describes how far the solver got, and is only called when cancelled
+    pub fn check(&self, partial: impl FnOnce() -> String) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Cancelled {
+                label: self.label.clone(),
+                partial: partial(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Report that `done` out of `total` items of work are complete, then check for cancellation
+    pub fn update(
+        &self,
+        done: usize,
+        total: usize,
+        partial: impl FnOnce() -> String,
+    ) -> Result<()> {
+        if total > 0 {
+            self.report(done as f64 / total as f64);
+        }
+        self.check(partial)
+    }
+
+    /// Clear the progress bar, if one was drawn
+    pub fn finish(&self) {
+        if self.show_bar && self.last_percent.take().is_some() {
+            let mut err = stderr().lock();
+            let _ = write!(err, "\r{}\r", " ".repeat(self.label.len() + BAR_WIDTH + 8));
+            let _ = err.flush();
+        }
+    }
+}
+
+impl Drop for Progress<'_> {
+    /// Clear the bar however the solver finished, so an error isn't printed after half a bar
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::{Cancelled, Progress};
+
+    #[test]
+    fn test_none_is_never_cancelled() {
+        let progress = Progress::none();
+        progress.report(0.5);
+
+        assert!(!progress.is_cancelled());
+        assert!(progress.update(5, 10, || unreachable!()).is_ok());
+    }
+
+    #[test]
+    fn test_cancelled_reports_partial_state() {
+        let flag = AtomicBool::new(false);
+        let progress = Progress::silent("Part 2", &flag);
+
+        assert!(progress.check(|| unreachable!()).is_ok());
+
+        flag.store(true, Ordering::Relaxed);
+        let err = progress.update(3, 10, || "row 3".to_string()).unwrap_err();
+
+        assert_eq!(
+            Some(&Cancelled {
+                label: "Part 2".to_string(),
+                partial: "row 3".to_string()
+            }),
+            err.downcast_ref::<Cancelled>()
+        );
+        assert_eq!("cancelled during Part 2: row 3", err.to_string());
+    }
+}
@@ -0,0 +1,394 @@
+//! A small property-based testing harness.
+//! Generate random values, check a property holds for all of them, and shrink any failure down to a
+//! minimal counterexample. Days can check their real solutions against a brute-force reference with
+//! `check_solution`.
+
+use std::{
+    fmt::Debug,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use crate::utils::solver_types::SolutionLinear;
+
+/// How many random cases to try by default
+pub const DEFAULT_CASES: usize = 1000;
+
+/// Give up shrinking after this many successful shrinks, in case shrinking doesn't converge
+const MAX_SHRINKS: usize = 10000;
+
+/// A xorshift random number generator. Not remotely cryptographic, but reproducible from a seed
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on 0
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// a random number in `lower..=upper`
+    pub fn range(&mut self, lower: i64, upper: i64) -> i64 {
+        assert!(lower <= upper, "empty range {}..={}", lower, upper);
+        let span = (upper - lower) as u64 + 1;
+        lower + (self.next_u64() % span) as i64
+    }
+
+    /// a random index below `upper`
+    pub fn below(&mut self, upper: usize) -> usize {
+        (self.next_u64() % upper as u64) as usize
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// a vector with a random length in `min_len..=max_len`, filled by `item`
+    pub fn vec<T>(
+        &mut self,
+        min_len: usize,
+        max_len: usize,
+        mut item: impl FnMut(&mut Rng) -> T,
+    ) -> Vec<T> {
+        let len = self.range(min_len as i64, max_len as i64) as usize;
+        (0..len).map(|_| item(self)).collect()
+    }
+}
+
+/// Values that can be made simpler, so a failing case can be reduced to a minimal counterexample
+pub trait Shrink: Clone {
+    /// simpler candidates for this value, most aggressive first
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! impl_shrink_signed {
+    ($($t:ty),*) => {
+        $(
+            impl Shrink for $t {
+                fn shrink(&self) -> Vec<Self> {
+                    let mut candidates = vec![0, self / 2, self - self.signum()];
+                    // MIN has no positive counterpart, so it can only shrink by halving or stepping
+                    if let Some(flipped) = self.checked_neg().filter(|_| *self < 0) {
+                        candidates.insert(1, flipped);
+                    }
+                    candidates.dedup();
+                    candidates.retain(|candidate| candidate != self);
+                    candidates
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_shrink_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Shrink for $t {
+                fn shrink(&self) -> Vec<Self> {
+                    if *self == 0 {
+                        return vec![];
+                    }
+                    let mut candidates = vec![0, self / 2, self - 1];
+                    candidates.dedup();
+                    candidates.retain(|candidate| candidate != self);
+                    candidates
+                }
+            }
+        )*
+    };
+}
+
+impl_shrink_signed!(i8, i16, i32, i64, isize);
+impl_shrink_unsigned!(u8, u16, u32, u64, usize);
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<Self> {
+        if *self {
+            vec![false]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl Shrink for char {
+    fn shrink(&self) -> Vec<Self> {
+        if *self == 'a' {
+            vec![]
+        } else {
+            vec!['a']
+        }
+    }
+}
+
+impl<T: Shrink> Shrink for Vec<T> {
+    fn shrink(&self) -> Vec<Self> {
+        let mut candidates = Vec::new();
+
+        if self.is_empty() {
+            return candidates;
+        }
+
+        // drop big chunks first, then single items, then simplify single items
+        candidates.push(vec![]);
+        let half = self.len() / 2;
+        if half > 0 {
+            candidates.push(self[..half].to_vec());
+            candidates.push(self[half..].to_vec());
+        }
+        for i in 0..self.len() {
+            let mut smaller = self.clone();
+            smaller.remove(i);
+            candidates.push(smaller);
+        }
+        for i in 0..self.len() {
+            for simpler in self[i].shrink() {
+                let mut candidate = self.clone();
+                candidate[i] = simpler;
+                candidates.push(candidate);
+            }
+        }
+
+        candidates
+    }
+}
+
+macro_rules! impl_shrink_tuple {
+    ($($name:ident: $idx:tt),*) => {
+        impl<$($name: Shrink),*> Shrink for ($($name,)*) {
+            fn shrink(&self) -> Vec<Self> {
+                let mut candidates = Vec::new();
+                $(
+                    for simpler in self.$idx.shrink() {
+                        let mut candidate = self.clone();
+                        candidate.$idx = simpler;
+                        candidates.push(candidate);
+                    }
+                )*
+                candidates
+            }
+        }
+    };
+}
+
+impl_shrink_tuple!(A: 0, B: 1);
+impl_shrink_tuple!(A: 0, B: 1, C: 2);
+impl_shrink_tuple!(A: 0, B: 1, C: 2, D: 3);
+
+/// Run a property against a value, treating a panic as a failure
+fn run_property<V>(property: &impl Fn(&V) -> Result<(), String>, value: &V) -> Result<(), String> {
+    match catch_unwind(AssertUnwindSafe(|| property(value))) {
+        Ok(result) => result,
+        Err(panic) => Err(match panic.downcast_ref::<&str>() {
+            Some(message) => format!("panicked: {}", message),
+            None => match panic.downcast_ref::<String>() {
+                Some(message) => format!("panicked: {}", message),
+                None => "panicked".to_string(),
+            },
+        }),
+    }
+}
+
+/// Check that `property` holds for `cases` values from `generate`.
+/// On failure, shrinks the value to a minimal counterexample and panics with it
+pub fn check<V: Shrink + Debug>(
+    cases: usize,
+    seed: u64,
+    generate: impl Fn(&mut Rng) -> V,
+    property: impl Fn(&V) -> Result<(), String>,
+) {
+    let mut rng = Rng::new(seed);
+
+    for case in 0..cases {
+        let value = generate(&mut rng);
+
+        if let Err(reason) = run_property(&property, &value) {
+            let (minimal, reason) = shrink_failure(value, reason, &property);
+            panic!(
+                "Property failed on case {} (seed {}).\nMinimal counterexample: {:#?}\nReason: {}",
+                case, seed, minimal, reason
+            );
+        }
+    }
+}
+
+/// Repeatedly replace a failing value with the first simpler value that still fails
+fn shrink_failure<V: Shrink>(
+    mut value: V,
+    mut reason: String,
+    property: &impl Fn(&V) -> Result<(), String>,
+) -> (V, String) {
+    for _ in 0..MAX_SHRINKS {
+        let simpler_failure = value.shrink().into_iter().find_map(|candidate| {
+            run_property(property, &candidate)
+                .err()
+                .map(|reason| (candidate, reason))
+        });
+
+        match simpler_failure {
+            Some((candidate, candidate_reason)) => {
+                value = candidate;
+                reason = candidate_reason;
+            }
+            None => break,
+        }
+    }
+    (value, reason)
+}
+
+/// A brute-force reference for a day, used to check the real solution against
+pub struct Oracle<V, S1, S2> {
+    /// generate a random puzzle, in a structured form that can be shrunk
+    pub generate: fn(&mut Rng) -> V,
+    /// puzzles that don't meet the puzzle's guarantees are skipped, including ones produced by shrinking
+    pub valid: fn(&V) -> bool,
+    /// write the puzzle out as puzzle input
+    pub to_input: fn(&V) -> String,
+    /// the expected answer to part 1
+    pub part1: fn(&V) -> S1,
+    /// the expected answer to part 2
+    pub part2: fn(&V) -> S2,
+}
+
+/// Check a linear solution's `part1` and `part2` agree with a brute-force reference on `cases` generated puzzles
+pub fn check_solution<S, I, S1, S2, V>(cases: usize, seed: u64, oracle: &Oracle<V, S1, S2>)
+where
+    S: SolutionLinear<I, S1, S2>,
+    S1: Debug + PartialEq + Clone,
+    S2: Debug + PartialEq,
+    V: Shrink + Debug,
+{
+    check(
+        cases,
+        seed,
+        |rng| loop {
+            let value = (oracle.generate)(rng);
+            if (oracle.valid)(&value) {
+                return value;
+            }
+        },
+        |value| {
+            if !(oracle.valid)(value) {
+                // shrunk into an impossible puzzle, so it can't be a counterexample
+                return Ok(());
+            }
+
+            let input = (oracle.to_input)(value);
+            let mut loaded = S::load(&input).map_err(|err| format!("load failed: {}", err))?;
+
+            let p1 = S::part1(&mut loaded).map_err(|err| format!("part 1 failed: {}", err))?;
+            let expected_1 = (oracle.part1)(value);
+            if p1 != expected_1 {
+                return Err(format!(
+                    "part 1 gave {:?}, expected {:?}, for input:\n{}",
+                    p1, expected_1, input
+                ));
+            }
+
+            let p2 = S::part2(&mut loaded, p1).map_err(|err| format!("part 2 failed: {}", err))?;
+            let expected_2 = (oracle.part2)(value);
+            if p2 != expected_2 {
+                return Err(format!(
+                    "part 2 gave {:?}, expected {:?}, for input:\n{}",
+                    p2, expected_2, input
+                ));
+            }
+
+            Ok(())
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::catch_unwind;
+
+    use super::{check, Rng, Shrink};
+    use rstest::rstest;
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let from_a = (0..10).map(|_| a.range(-5, 5)).collect::<Vec<_>>();
+        let from_b = (0..10).map(|_| b.range(-5, 5)).collect::<Vec<_>>();
+
+        assert_eq!(from_a, from_b);
+        assert!(from_a.iter().all(|v| (-5..=5).contains(v)));
+    }
+
+    #[rstest]
+    #[case(10, vec![0, 5, 9])]
+    #[case(-10, vec![0, 10, -5, -9])]
+    #[case(1, vec![0])]
+    #[case(0, vec![])]
+    #[case(i32::MIN, vec![0, i32::MIN / 2, i32::MIN + 1])]
+    fn validate_shrink_int(#[case] value: i32, #[case] expected: Vec<i32>) {
+        assert_eq!(expected, value.shrink());
+    }
+
+    #[test]
+    fn test_passing_property() {
+        check(
+            100,
+            1,
+            |rng| rng.vec(0, 10, |rng| rng.range(0, 100)),
+            |v| {
+                if v.iter().all(|x| *x >= 0) {
+                    Ok(())
+                } else {
+                    Err("negative".to_string())
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_failure_is_shrunk() {
+        let result = catch_unwind(|| {
+            check(
+                100,
+                1,
+                |rng| rng.vec(0, 10, |rng| rng.range(0, 100)),
+                |v: &Vec<i64>| {
+                    if v.iter().any(|x| *x >= 50) {
+                        Err("too big".to_string())
+                    } else {
+                        Ok(())
+                    }
+                },
+            )
+        });
+
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("Minimal counterexample: [\n    50,\n]"));
+        assert!(message.contains("Reason: too big"));
+    }
+
+    #[test]
+    fn test_failing_min_is_shrunk() {
+        let result = catch_unwind(|| {
+            check(
+                10,
+                1,
+                |_| i64::MIN,
+                |v: &i64| {
+                    if *v < 0 {
+                        Err("negative".to_string())
+                    } else {
+                        Ok(())
+                    }
+                },
+            )
+        });
+
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("Minimal counterexample: -1"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Regions of connected points: flood fill, labelling the separate regions of a `Grid`, and
+//! measuring a region's perimeter (or surface area, in 3D).
+
+use std::collections::HashSet;
+
+use super::{
+    grid::{Adjacency, Grid},
+    point::{Pt, Scalar},
+};
+
+/// Every point reachable from `start` through points where `inside` holds, without leaving
+/// `bounds`. Empty if `start` itself isn't inside
+pub fn fill<const DIMS: usize, S: Scalar>(
+    start: Pt<DIMS, S>,
+    adjacency: Adjacency,
+    (mins, maxs): ([S; DIMS], [S; DIMS]),
+    inside: impl Fn(&Pt<DIMS, S>) -> bool,
+) -> HashSet<Pt<DIMS, S>> {
+    let in_bounds = |pt: &Pt<DIMS, S>| (0..DIMS).all(|i| mins[i] <= pt.0[i] && pt.0[i] <= maxs[i]);
+    let offsets = adjacency.offsets::<DIMS, S>();
+
+    let mut filled = HashSet::new();
+    let mut to_visit = vec![start];
+
+    while let Some(pt) = to_visit.pop() {
+        if !in_bounds(&pt) || !inside(&pt) || !filled.insert(pt) {
+            continue;
+        }
+        to_visit.extend(offsets.iter().map(|offset| pt + *offset));
+    }
+
+    filled
+}
+
+/// Every point within the bounds of the stored points reachable from `start` through points whose
+/// value (or the default) matches `predicate`. Empty if nothing is stored
+#[allow(dead_code)]
+pub fn flood_fill<T: Copy, const DIMS: usize, S: Scalar>(
+    grid: &Grid<T, DIMS, S>,
+    start: Pt<DIMS, S>,
+    adjacency: Adjacency,
+    predicate: impl Fn(T) -> bool,
+) -> HashSet<Pt<DIMS, S>> {
+    let Some(bounds) = grid.stored_bounds() else {
+        return HashSet::new();
+    };
+    fill(start, adjacency, bounds, |pt| predicate(grid.get_def(pt)))
+}
+
+/// The separate regions made up of the points stored in the grid whose values match `predicate`,
+/// e.g. to count islands. A region's index is its label, in no particular order
+#[allow(dead_code)]
+pub fn components<T: Copy, const DIMS: usize, S: Scalar>(
+    grid: &Grid<T, DIMS, S>,
+    adjacency: Adjacency,
+    predicate: impl Fn(T) -> bool,
+) -> Vec<HashSet<Pt<DIMS, S>>> {
+    let Some(bounds) = grid.stored_bounds() else {
+        return Vec::new();
+    };
+    let inside = |pt: &Pt<DIMS, S>| grid.grid.get(pt).map_or(false, |value| predicate(*value));
+
+    let mut labelled = HashSet::new();
+    let mut regions = Vec::new();
+
+    for pt in grid.grid.keys() {
+        if labelled.contains(pt) || !inside(pt) {
+            continue;
+        }
+        let region = fill(*pt, adjacency, bounds, inside);
+        labelled.extend(region.iter().copied());
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// The number of faces of `region` that don't touch another point in it: the perimeter in 2D, or
+/// the surface area in 3D. Counts the faces of any holes inside it too
+#[allow(dead_code)]
+pub fn surface_area<const DIMS: usize, S: Scalar>(region: &HashSet<Pt<DIMS, S>>) -> usize {
+    let offsets = Pt::<DIMS, S>::card_offsets();
+    region
+        .iter()
+        .map(|pt| {
+            offsets
+                .iter()
+                .filter(|offset| !region.contains(&(*pt + **offset)))
+                .count()
+        })
+        .sum()
+}
+
+/// The points around `region` that can reach outside it, within its bounds grown by 1 in every
+/// direction. Anything else not in `region` is enclosed by it
+#[allow(dead_code)]
+pub fn exterior<const DIMS: usize, S: Scalar>(
+    region: &HashSet<Pt<DIMS, S>>,
+    adjacency: Adjacency,
+) -> HashSet<Pt<DIMS, S>> {
+    let Some(first) = region.iter().next() else {
+        return HashSet::new();
+    };
+
+    let mut mins = first.0;
+    let mut maxs = first.0;
+    for pt in region {
+        for i in 0..DIMS {
+            mins[i] = mins[i].min(pt.0[i] - S::one());
+            maxs[i] = maxs[i].max(pt.0[i] + S::one());
+        }
+    }
+
+    fill(Pt(mins), adjacency, (mins, maxs), |pt| !region.contains(pt))
+}
+
+/// Like `surface_area`, but only counting the faces that can be reached from outside `region`,
+/// e.g. leaving out air pockets trapped inside a lump of lava
+#[allow(dead_code)]
+pub fn exterior_surface_area<const DIMS: usize, S: Scalar>(region: &HashSet<Pt<DIMS, S>>) -> usize {
+    let outside = exterior(region, Adjacency::Cardinal);
+    let offsets = Pt::<DIMS, S>::card_offsets();
+    region
+        .iter()
+        .map(|pt| {
+            offsets
+                .iter()
+                .filter(|offset| outside.contains(&(*pt + **offset)))
+                .count()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use itertools::Itertools;
+
+    use super::{components, exterior, exterior_surface_area, flood_fill, surface_area};
+    use crate::utils::{
+        grid::{Adjacency, Grid},
+        point::Pt,
+    };
+
+    const ISLANDS: &str = "\
+##...
+##.#.
+...#.
+#....
+.#..#";
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = Grid::from_text(ISLANDS, |c| c);
+
+        let sea = flood_fill(&grid, Pt([2, 0]), Adjacency::Cardinal, |c| c == '.');
+        assert_eq!(15, sea.len());
+        assert!(flood_fill(&grid, Pt([0, 0]), Adjacency::Cardinal, |c| c == '.').is_empty());
+
+        // stays inside the bounds of the grid, even though the default matches
+        let mut open = Grid::<char, 2>::from(vec![(Pt([0, 0]), '.'), (Pt([2, 2]), '#')]);
+        open.set_default('.');
+        assert_eq!(
+            8,
+            flood_fill(&open, Pt([0, 0]), Adjacency::Full, |c| c == '.').len()
+        );
+
+        // and doesn't stretch them out to the origin
+        let mut away = Grid::<char, 2>::from(vec![(Pt([5, 5]), '.'), (Pt([7, 6]), '#')]);
+        away.set_default('.');
+        let filled = flood_fill(&away, Pt([5, 5]), Adjacency::Cardinal, |c| c == '.');
+        assert_eq!(5, filled.len());
+        assert!(!filled.contains(&Pt([4, 5])));
+        assert!(flood_fill(
+            &Grid::<char, 2>::default(),
+            Pt([0, 0]),
+            Adjacency::Full,
+            |_| true
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_components() {
+        let grid = Grid::from_text(ISLANDS, |c| c);
+
+        let sizes = |adjacency| {
+            components(&grid, adjacency, |c| c == '#')
+                .iter()
+                .map(|region| region.len())
+                .sorted()
+                .collect_vec()
+        };
+        assert_eq!(vec![1, 1, 1, 2, 4], sizes(Adjacency::Cardinal));
+        // the two on their own in the corner touch diagonally
+        assert_eq!(vec![1, 2, 2, 4], sizes(Adjacency::Full));
+
+        let islands = components(&grid, Adjacency::Cardinal, |c| c == '#');
+        let square = islands.iter().find(|region| region.len() == 4).unwrap();
+        assert_eq!(8, surface_area(square));
+    }
+
+    #[test]
+    fn test_enclosed() {
+        // a ring with a hole in the middle
+        let ring: HashSet<Pt<2>> = (0..3)
+            .cartesian_product(0..3)
+            .filter(|&(x, y)| (x, y) != (1, 1))
+            .map(|(x, y)| Pt([x, y]))
+            .collect();
+
+        assert_eq!(16, surface_area(&ring));
+        assert_eq!(12, exterior_surface_area(&ring));
+
+        let outside = exterior(&ring, Adjacency::Cardinal);
+        assert_eq!(25 - 9, outside.len());
+        assert!(!outside.contains(&Pt([1, 1])));
+    }
+
+    #[test]
+    fn test_lava_droplet() {
+        let cubes: HashSet<Pt<3>> = "2,2,2 1,2,2 3,2,2 2,1,2 2,3,2 2,2,1 2,2,3 2,2,4 2,2,6 1,2,5 \
+            3,2,5 2,1,5 2,3,5"
+            .split_whitespace()
+            .map(|cube| cube.parse().unwrap())
+            .collect();
+
+        assert_eq!(64, surface_area(&cubes));
+        assert_eq!(58, exterior_surface_area(&cubes));
+    }
+}
@@ -0,0 +1,335 @@
+use std::io::{BufRead, Write};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// A puzzle that is solved by repeatedly advancing some state one step at a time
+pub trait Simulation {
+    /// advance the simulation by a single step
+    fn step(&mut self) -> Result<()>;
+
+    /// whether the simulation has finished
+    fn is_done(&self) -> bool;
+
+    /// a human-readable picture of the current state
+    fn render(&self) -> String;
+
+    /// named values describing the current state, used by the debugger's `inspect` and `until` commands
+    fn watches(&self) -> Vec<(String, i64)> {
+        vec![]
+    }
+}
+
+/// Step a simulation until it reports that it is done
+pub fn run_to_completion<S: Simulation + ?Sized>(sim: &mut S) -> Result<()> {
+    while !sim.is_done() {
+        sim.step()?;
+    }
+    Ok(())
+}
+
+const HELP: &str = "Commands:
+  s, step [N]          advance one (or N) steps. An empty line also advances one step
+  u, until done        advance until the simulation is done
+  u, until NAME OP VAL advance until a watched value satisfies the condition, e.g. `until x >= 20`
+                       OP is one of = != < <= > >=
+  p, print             render the current state
+  i, inspect           show the watched values
+  h, help              show this message
+  q, quit              stop debugging";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A condition on a watched value, e.g. `landed >= 20`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Condition {
+    name: String,
+    comparison: Comparison,
+    value: i64,
+}
+
+impl Condition {
+    fn parse(input: &str) -> Result<Condition> {
+        // two character operators must be tried before their one character prefixes
+        let operators = [
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("!=", Comparison::Ne),
+            ("=", Comparison::Eq),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ];
+
+        for (symbol, comparison) in operators {
+            if let Some((name, value)) = input.split_once(symbol) {
+                let name = name.trim();
+                if name.is_empty() {
+                    return Err(anyhow!("Missing a watch name in condition '{}'", input));
+                }
+                let value = value.trim().parse::<i64>().map_err(|_| {
+                    anyhow!(
+                        "Expected a number after '{}', got '{}'",
+                        symbol,
+                        value.trim()
+                    )
+                })?;
+
+                return Ok(Condition {
+                    name: name.to_string(),
+                    comparison,
+                    value,
+                });
+            }
+        }
+
+        Err(anyhow!(
+            "Could not find an operator in condition '{}'",
+            input
+        ))
+    }
+
+    fn holds(&self, watches: &[(String, i64)]) -> Result<bool> {
+        let (_, current) = watches
+            .iter()
+            .find(|(name, _)| *name == self.name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown watch '{}'. Available: {}",
+                    self.name,
+                    watches.iter().map(|(name, _)| name).join(", ")
+                )
+            })?;
+
+        Ok(match self.comparison {
+            Comparison::Eq => *current == self.value,
+            Comparison::Ne => *current != self.value,
+            Comparison::Lt => *current < self.value,
+            Comparison::Le => *current <= self.value,
+            Comparison::Gt => *current > self.value,
+            Comparison::Ge => *current >= self.value,
+        })
+    }
+}
+
+fn format_watches(watches: &[(String, i64)]) -> String {
+    if watches.is_empty() {
+        return "(no watched values)".to_string();
+    }
+    watches
+        .iter()
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .join(", ")
+}
+
+/// Interactively step through a simulation.
+/// Commands are read line by line from `input`, and the state is reported to `output`
+pub fn debug_simulation<S: Simulation + ?Sized>(
+    sim: &mut S,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<()> {
+    let mut steps: usize = 0;
+
+    writeln!(output, "{}", HELP)?;
+    write!(output, "> ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        let words = line.split_whitespace().collect_vec();
+
+        let mut advanced = true;
+        // a step that fails stops there, leaving its state to be inspected
+        let mut failed = None;
+        match words.as_slice() {
+            [] | ["s" | "step"] => failed = advance(sim, &mut steps, 1).err(),
+            ["s" | "step", count] => match count.parse::<usize>() {
+                Ok(count) => failed = advance(sim, &mut steps, count).err(),
+                Err(_) => {
+                    advanced = false;
+                    writeln!(output, "Expected a number of steps, got '{}'", count)?;
+                }
+            },
+            ["u" | "until", "done"] => failed = advance(sim, &mut steps, usize::MAX).err(),
+            ["u" | "until", condition @ ..] => match Condition::parse(&condition.join(" ")) {
+                Ok(condition) => {
+                    while !sim.is_done() {
+                        match condition.holds(&sim.watches()) {
+                            Ok(true) => break,
+                            Ok(false) => {
+                                failed = advance(sim, &mut steps, 1).err();
+                                if failed.is_some() {
+                                    break;
+                                }
+                            }
+                            Err(err) => {
+                                writeln!(output, "{}", err)?;
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(err) => {
+                    advanced = false;
+                    writeln!(output, "{}", err)?;
+                }
+            },
+            ["p" | "print"] => {
+                advanced = false;
+                writeln!(output, "{}", sim.render())?;
+            }
+            ["i" | "inspect"] => {
+                advanced = false;
+                writeln!(output, "{}", format_watches(&sim.watches()))?;
+            }
+            ["h" | "help"] => {
+                advanced = false;
+                writeln!(output, "{}", HELP)?;
+            }
+            ["q" | "quit"] => return Ok(()),
+            _ => {
+                advanced = false;
+                writeln!(output, "Unknown command '{}'. Type 'h' for help.", line)?;
+            }
+        }
+
+        if let Some(err) = failed {
+            writeln!(output, "Step {} failed: {:#}", steps + 1, err)?;
+        }
+        if advanced {
+            writeln!(output, "step {}: {}", steps, format_watches(&sim.watches()))?;
+            if sim.is_done() {
+                writeln!(output, "Simulation is done after {} steps", steps)?;
+            }
+        }
+
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+/// advance up to `count` steps, stopping early if the simulation finishes
+fn advance<S: Simulation + ?Sized>(sim: &mut S, steps: &mut usize, count: usize) -> Result<()> {
+    for _ in 0..count {
+        if sim.is_done() {
+            break;
+        }
+        sim.step()?;
+        *steps += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{debug_simulation, run_to_completion, Simulation};
+    use anyhow::{anyhow, Result};
+    use rstest::rstest;
+
+    /// counts up to a limit
+    struct Counter {
+        value: i64,
+        limit: i64,
+        /// the value it can't step past, to test errors
+        fails_at: Option<i64>,
+    }
+
+    impl Simulation for Counter {
+        fn step(&mut self) -> Result<()> {
+            if Some(self.value) == self.fails_at {
+                return Err(anyhow!("stuck at {}", self.value));
+            }
+            self.value += 1;
+            Ok(())
+        }
+
+        fn is_done(&self) -> bool {
+            self.value >= self.limit
+        }
+
+        fn render(&self) -> String {
+            format!("[{}]", self.value)
+        }
+
+        fn watches(&self) -> Vec<(String, i64)> {
+            vec![("value".to_string(), self.value)]
+        }
+    }
+
+    fn debug(commands: &str, limit: i64, fails_at: Option<i64>) -> (i64, String) {
+        let mut sim = Counter {
+            value: 0,
+            limit,
+            fails_at,
+        };
+        let mut output = Vec::new();
+        debug_simulation(&mut sim, Cursor::new(commands), &mut output).unwrap();
+        (sim.value, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_run_to_completion() {
+        let mut sim = Counter {
+            value: 0,
+            limit: 42,
+            fails_at: None,
+        };
+        run_to_completion(&mut sim).unwrap();
+        assert_eq!(42, sim.value);
+    }
+
+    #[rstest]
+    #[case("s\n", 1)]
+    #[case("\n\n\n", 3)]
+    #[case("step 7\n", 7)]
+    #[case("s 5\nq\ns 5\n", 5)]
+    #[case("until value >= 12\n", 12)]
+    #[case("u value=3\ns\n", 4)]
+    #[case("until done\n", 100)]
+    #[case("s 1000\n", 100)]
+    #[case("p\ni\nh\n", 0)]
+    fn validate_debugger_steps(#[case] commands: &str, #[case] expected: i64) {
+        let (value, _) = debug(commands, 100, None);
+        assert_eq!(expected, value);
+    }
+
+    #[test]
+    fn test_debugger_output() {
+        let (_, output) = debug(
+            "s 2\np\nu missing > 1\nu value\nbogus\nuntil done\n",
+            3,
+            None,
+        );
+
+        assert!(output.contains("step 2: value = 2"));
+        assert!(output.contains("[2]"));
+        assert!(output.contains("Unknown watch 'missing'. Available: value"));
+        assert!(output.contains("Could not find an operator in condition 'value'"));
+        assert!(output.contains("Unknown command 'bogus'"));
+        assert!(output.contains("Simulation is done after 3 steps"));
+    }
+
+    #[test]
+    fn test_debugger_survives_a_failed_step() {
+        let (value, output) = debug("s 20\ni\nu value > 15\np\n", 100, Some(13));
+
+        assert_eq!(13, value);
+        assert!(output.contains("Step 14 failed: stuck at 13\nstep 13: value = 13"));
+        // still at the prompt, with the state that failed to step
+        assert!(output.contains("> value = 13"));
+        assert_eq!(2, output.matches("Step 14 failed").count());
+        assert!(output.contains("[13]"));
+    }
+}
@@ -1,19 +1,42 @@
 use anyhow::Result;
 use std::{fmt::Debug, time::Instant};
 
+use super::progress::{Progress, CANCELLED};
+
+/// The answers to a solved day, and how long it took
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solved {
+    /// the debug representation of part 1's solution
+    pub part1: String,
+    /// the debug representation of part 2's solution
+    pub part2: String,
+    /// the total time elapsed in milliseconds
+    pub time: f32,
+}
+
 ////////////// SOLUTION LINEAR
 /// When a day has two parts that must be solved sequentially
 pub trait SolutionLinear<I, S1: Debug, S2: Debug> {
     fn load(input: &str) -> Result<I>;
     fn part1(input: &mut I) -> Result<S1>;
     fn part2(input: &mut I, part_1_solution: S1) -> Result<S2>;
+
+    /// `part1`, with a `Progress` to report to. Override this for long-running parts
+    fn part1_with_progress(input: &mut I, _progress: &Progress) -> Result<S1> {
+        Self::part1(input)
+    }
+
+    /// `part2`, with a `Progress` to report to. Override this for long-running parts
+    fn part2_with_progress(input: &mut I, part_1_solution: S1, _progress: &Progress) -> Result<S2> {
+        Self::part2(input, part_1_solution)
+    }
 }
 
 /// Solve a day where part 2 depends on the output of part 1.
-/// Returns the total time elapsed in milliseconds
+/// Returns the solutions and the total time elapsed
 pub fn solve_linear<S: SolutionLinear<I, S1, S2>, I, S1: Debug, S2: Debug>(
     input: &str,
-) -> Result<f32> {
+) -> Result<Solved> {
     let start = Instant::now();
 
     let mut input = S::load(input)?;
@@ -24,16 +47,19 @@ pub fn solve_linear<S: SolutionLinear<I, S1, S2>, I, S1: Debug, S2: Debug>(
 
     let p1_start = Instant::now();
 
-    let p1 = S::part1(&mut input)?;
+    // the bar is cleared when the temporary Progress is dropped, before anything is printed
+    let p1 = S::part1_with_progress(&mut input, &Progress::new("Part 1", &CANCELLED))?;
 
     let p1_end = p1_start.elapsed().as_secs_f32() * 1000.0;
 
-    println!("Part 1 Solution: \t{:?}", p1);
+    let part1 = format!("{:?}", p1);
+
+    println!("Part 1 Solution: \t{}", part1);
     println!("Part 1 solved in:\t{}ms", p1_end);
 
     let p2_start = Instant::now();
 
-    let p2 = S::part2(&mut input, p1)?;
+    let p2 = S::part2_with_progress(&mut input, p1, &Progress::new("Part 2", &CANCELLED))?;
 
     let p2_end = p2_start.elapsed().as_secs_f32() * 1000.0;
 
@@ -44,7 +70,11 @@ pub fn solve_linear<S: SolutionLinear<I, S1, S2>, I, S1: Debug, S2: Debug>(
 
     println!("Overall time:\t\t{}ms", solved_in);
 
-    Ok(solved_in)
+    Ok(Solved {
+        part1,
+        part2: format!("{:?}", p2),
+        time: solved_in,
+    })
 }
 
 ////////////// SOLUTION SIMULTANEOUS
@@ -52,13 +82,18 @@ pub fn solve_linear<S: SolutionLinear<I, S1, S2>, I, S1: Debug, S2: Debug>(
 pub trait SolutionSimultaneous<I, S1: Debug, S2: Debug> {
     fn load(input: &str) -> Result<I>;
     fn solve(input: I) -> Result<(S1, S2)>;
+
+    /// `solve`, with a `Progress` to report to. Override this for long-running days
+    fn solve_with_progress(input: I, _progress: &Progress) -> Result<(S1, S2)> {
+        Self::solve(input)
+    }
 }
 
 /// Solve a day where part 1 and part 2 can be solved simultaneously
-/// Returns the total time elapsed in milliseconds
+/// Returns the solutions and the total time elapsed
 pub fn solve_simultaneous<S: SolutionSimultaneous<I, S1, S2>, I, S1: Debug, S2: Debug>(
     input: &str,
-) -> Result<f32> {
+) -> Result<Solved> {
     let start = Instant::now();
 
     let input = S::load(input)?;
@@ -69,7 +104,7 @@ pub fn solve_simultaneous<S: SolutionSimultaneous<I, S1, S2>, I, S1: Debug, S2:
 
     let start_solving = Instant::now();
 
-    let (p1, p2) = S::solve(input)?;
+    let (p1, p2) = S::solve_with_progress(input, &Progress::new("Solving", &CANCELLED))?;
 
     let solved = start_solving.elapsed().as_secs_f32() * 1000.0;
 
@@ -82,5 +117,9 @@ pub fn solve_simultaneous<S: SolutionSimultaneous<I, S1, S2>, I, S1: Debug, S2:
 
     println!("Overall time:\t\t{}ms", solved_in);
 
-    Ok(solved_in)
+    Ok(Solved {
+        part1: format!("{:?}", p1),
+        part2: format!("{:?}", p2),
+        time: solved_in,
+    })
 }